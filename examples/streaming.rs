@@ -0,0 +1,34 @@
+use std::io::Write;
+use turbine_llm::{LLMRequest, Message, TurbineClient};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== Streaming Example ===\n");
+
+    let client = TurbineClient::from_model("openai/gpt-4o-mini")?;
+
+    let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user(
+        "Write a haiku about the Rust borrow checker.",
+    ));
+
+    let mut stream = client.send_request_stream(&request).await?;
+
+    // Print text as it arrives; the last chunk carries usage and finish reason
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if chunk.done {
+            println!("\n\nFinish reason: {:?}", chunk.finish_reason);
+            if let Some(usage) = chunk.usage {
+                println!(
+                    "Token usage: {} input, {} output",
+                    usage.input_tokens, usage.output_tokens
+                );
+            }
+        } else {
+            print!("{}", chunk.delta);
+            std::io::stdout().flush()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! An append-only JSON Lines log of completed requests and streams.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::{
+    error::Result,
+    models::{LLMRequest, LLMResponse},
+};
+
+/// Appends every completed interaction to a JSON Lines file.
+///
+/// Each line is one object with the `timestamp` (milliseconds since the Unix epoch), the
+/// `model`, the `request` (system prompt, messages and sampling settings), the full
+/// `response` and its `usage`. Lines are added whole under a lock, so a logger can be
+/// shared by concurrent requests. An existing file is appended to.
+///
+/// Lines are buffered in memory and written when the buffer fills, so logging rarely
+/// touches the disk on the request path. Call [`flush`](Self::flush) (or
+/// [`TurbineClient::shutdown`](crate::TurbineClient::shutdown)) to write the buffer out;
+/// dropping the logger writes it too, but ignores errors.
+///
+/// Usually attached with
+/// [`TurbineClient::with_audit_logger`](crate::TurbineClient::with_audit_logger).
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::audit::AuditLogger;
+/// use turbine_llm::providers::echo::EchoProvider;
+/// use turbine_llm::{LLMRequest, TurbineClient};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let path = std::env::temp_dir().join("turbine-audit-doctest.jsonl");
+/// # let _ = std::fs::remove_file(&path);
+/// let client = TurbineClient::from_provider(EchoProvider::new())
+///     .with_audit_logger(AuditLogger::new(&path)?);
+///
+/// client.send_request(&LLMRequest::quick("echo", "Hello")).await?;
+/// // Streams are logged once read to the end
+/// let stream = client.send_request_stream(&LLMRequest::quick("echo", "Streamed")).await?;
+/// stream.into_response().await?;
+/// client.shutdown().await?;
+///
+/// let log = std::fs::read_to_string(&path)?;
+/// let entries: Vec<serde_json::Value> =
+///     log.lines().map(serde_json::from_str).collect::<Result<_, _>>()?;
+/// assert_eq!(entries.len(), 2);
+/// assert_eq!(entries[0]["model"], "echo");
+/// assert_eq!(entries[0]["request"]["messages"][0]["content"], "Hello");
+/// assert_eq!(entries[0]["response"]["content"], "Hello");
+/// assert_eq!(entries[1]["response"]["content"], "Streamed");
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AuditLogger {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl AuditLogger {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    /// Returns the path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends one interaction to the log.
+    pub fn log(&self, request: &LLMRequest, response: &LLMResponse) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let entry = json!({
+            "timestamp": timestamp,
+            "model": request.model,
+            "request": {
+                "system_prompt": request.system_prompt,
+                "messages": request.messages,
+                "max_tokens": request.effective_max_tokens(),
+                "temperature": request.temperature,
+                "top_p": request.top_p,
+            },
+            "response": response,
+            "usage": response.usage,
+        });
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        writer.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Writes the buffered lines to the file and waits until its contents have reached the
+    /// disk, so they survive the process exiting or the machine crashing.
+    pub fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! A blocking API for synchronous code.
+//!
+//! [`TurbineClient`] mirrors [`crate::TurbineClient`] but each method blocks until the
+//! request completes, so scripts and CLIs can use Turbine without setting up an async
+//! runtime. Every client runs its requests on a private single-threaded Tokio runtime.
+//!
+//! Requires the `blocking` feature.
+//!
+//! # Panics
+//!
+//! The methods panic when called from within an async runtime, such as inside
+//! `#[tokio::main]` or a spawned task. Use [`crate::TurbineClient`] there instead, or call the
+//! blocking client from [`tokio::task::spawn_blocking`].
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//! use turbine_llm::blocking::TurbineClient;
+//! use turbine_llm::providers::openai::OpenAIProvider;
+//! use turbine_llm::transport::{HttpResponse, MockTransport};
+//! use turbine_llm::LLMRequest;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+//!     200,
+//!     r#"{"choices":[{"message":{"content":"Hello!"}}],"usage":{"prompt_tokens":3,"completion_tokens":2}}"#,
+//! )));
+//! let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock))?;
+//!
+//! // No runtime needed
+//! let response = client.send_request(&LLMRequest::quick("gpt-4o-mini", "Hi"))?;
+//! assert_eq!(response.content, "Hello!");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    client,
+    embeddings::{EmbeddingRequest, EmbeddingResponse},
+    error::Result,
+    models::{LLMRequest, LLMResponse},
+    providers::LLMProviderTrait,
+    types::Provider,
+};
+
+/// A client whose methods block until the response is available.
+///
+/// See the [module documentation](self) for when it may be used.
+pub struct TurbineClient {
+    inner: client::TurbineClient,
+    runtime: Runtime,
+}
+
+impl TurbineClient {
+    /// Creates a client for `provider`, reading the API key from the environment.
+    ///
+    /// See [`crate::TurbineClient::new`].
+    pub fn new(provider: Provider) -> Result<Self> {
+        Self::from_async(client::TurbineClient::new(provider)?)
+    }
+
+    /// Creates a client for `provider` with an explicit API key.
+    ///
+    /// See [`crate::TurbineClient::new_with_key`].
+    pub fn new_with_key(provider: Provider, api_key: impl Into<String>) -> Result<Self> {
+        Self::from_async(client::TurbineClient::new_with_key(provider, api_key))
+    }
+
+    /// Creates a client with a default model from a `"provider/model"` string.
+    ///
+    /// See [`crate::TurbineClient::from_model`].
+    pub fn from_model(model_str: &str) -> Result<Self> {
+        Self::from_async(client::TurbineClient::from_model(model_str)?)
+    }
+
+    /// Creates a client with a default model and an explicit API key.
+    ///
+    /// See [`crate::TurbineClient::from_model_with_key`].
+    pub fn from_model_with_key(model_str: &str, api_key: impl Into<String>) -> Result<Self> {
+        Self::from_async(client::TurbineClient::from_model_with_key(
+            model_str, api_key,
+        )?)
+    }
+
+    /// Creates a client backed by a custom provider implementation.
+    pub fn from_provider(provider: impl LLMProviderTrait + 'static) -> Result<Self> {
+        Self::from_async(client::TurbineClient::from_provider(provider))
+    }
+
+    /// Wraps an async client, keeping its configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::IoError`](crate::TurbineError::IoError) if the runtime cannot
+    /// be created.
+    pub fn from_async(inner: client::TurbineClient) -> Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Returns the wrapped async client.
+    pub fn as_async(&self) -> &client::TurbineClient {
+        &self.inner
+    }
+
+    /// Sends a request and waits for the response.
+    ///
+    /// See [`crate::TurbineClient::send_request`].
+    pub fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        self.block_on(self.inner.send_request(request))
+    }
+
+    /// Sends a message to the default model.
+    ///
+    /// See [`crate::TurbineClient::send`].
+    pub fn send(&self, message: &str) -> Result<LLMResponse> {
+        self.block_on(self.inner.send(message))
+    }
+
+    /// Sends a message with a system prompt to the default model.
+    ///
+    /// See [`crate::TurbineClient::send_with_system`].
+    pub fn send_with_system(&self, system_prompt: &str, message: &str) -> Result<LLMResponse> {
+        self.block_on(self.inner.send_with_system(system_prompt, message))
+    }
+
+    /// Sends several requests concurrently and returns their results in order.
+    ///
+    /// See [`crate::TurbineClient::send_batch`].
+    pub fn send_batch(
+        &self,
+        requests: &[LLMRequest],
+        concurrency: usize,
+    ) -> Vec<Result<LLMResponse>> {
+        self.block_on(self.inner.send_batch(requests, concurrency))
+    }
+
+    /// Streams a response, calling `on_token` with each text delta, and returns the
+    /// assembled response.
+    ///
+    /// See [`crate::TurbineClient::send_streaming_collect`].
+    pub fn send_streaming_collect<F>(
+        &self,
+        request: &LLMRequest,
+        on_token: F,
+    ) -> Result<LLMResponse>
+    where
+        F: FnMut(&str),
+    {
+        self.block_on(self.inner.send_streaming_collect(request, on_token))
+    }
+
+    /// Classifies `text` into one of `labels`.
+    ///
+    /// See [`crate::TurbineClient::classify`].
+    pub fn classify(&self, model: &str, text: &str, labels: &[&str]) -> Result<String> {
+        self.block_on(self.inner.classify(model, text, labels))
+    }
+
+    /// Embeds the texts of `request`.
+    ///
+    /// See [`crate::TurbineClient::embed`].
+    pub fn embed(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        self.block_on(self.inner.embed(request))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
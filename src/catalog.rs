@@ -0,0 +1,446 @@
+//! Static metadata about known models.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, TurbineError},
+    models::LLMRequest,
+    types::Provider,
+};
+
+/// The model data bundled with the crate.
+const BUNDLED_MODEL_DATA: &str = include_str!("../data/models.json");
+
+/// The model data in use: the bundled data plus any loaded with [`load_model_data`].
+static MODEL_DATA: LazyLock<RwLock<ModelData>> = LazyLock::new(|| {
+    RwLock::new(ModelData::parse(BUNDLED_MODEL_DATA).expect("bundled model data is valid"))
+});
+
+/// Context windows and prices keyed by model name prefix, as stored in a model data file.
+///
+/// Lookups use the longest prefix that has the wanted value, so `gpt-4o-mini` can list only
+/// its price and inherit the context window of `gpt-4o`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ModelData {
+    models: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModelEntry {
+    prefix: String,
+    #[serde(default)]
+    context_window: Option<u32>,
+    #[serde(default)]
+    input_per_million: Option<f64>,
+    #[serde(default)]
+    output_per_million: Option<f64>,
+}
+
+impl ModelData {
+    fn parse(json: &str) -> Result<Self> {
+        let data: Self = serde_json::from_str(json)?;
+        for entry in &data.models {
+            let invalid = |problem: &str| {
+                Err(TurbineError::InvalidRequest(format!(
+                    "Invalid model data for `{}`: {}",
+                    entry.prefix, problem
+                )))
+            };
+            if entry.prefix.is_empty() {
+                return invalid("empty prefix");
+            }
+            if entry.context_window == Some(0) {
+                return invalid("context window of 0");
+            }
+            if entry.input_per_million.is_some() != entry.output_per_million.is_some() {
+                return invalid("input and output prices must be given together");
+            }
+            let prices = [entry.input_per_million, entry.output_per_million];
+            if prices
+                .into_iter()
+                .flatten()
+                .any(|price| !price.is_finite() || price < 0.0)
+            {
+                return invalid("negative or non-finite price");
+            }
+        }
+        Ok(data)
+    }
+
+    /// Adds the entries of `other`, replacing entries with the same prefix.
+    fn merge(&mut self, other: ModelData) {
+        for entry in other.models {
+            match self
+                .models
+                .iter_mut()
+                .find(|existing| existing.prefix.eq_ignore_ascii_case(&entry.prefix))
+            {
+                Some(existing) => *existing = entry,
+                None => self.models.push(entry),
+            }
+        }
+    }
+
+    /// Returns `value` of the entry with the longest prefix of `model` that has one.
+    fn lookup<T>(&self, model: &str, value: impl Fn(&ModelEntry) -> Option<T>) -> Option<T> {
+        let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+
+        self.models
+            .iter()
+            .filter(|entry| name.starts_with(&entry.prefix.to_lowercase()))
+            .filter_map(|entry| Some((entry.prefix.len(), value(entry)?)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Loads context windows and prices from the JSON file at `path`, on top of the bundled
+/// data, so that new models or changed prices can be used without a crate release.
+///
+/// The file has the format of the bundled `data/models.json`: a `models` array whose
+/// entries have a `prefix` and optionally a `context_window` and an `input_per_million`
+/// and `output_per_million` price in US dollars. An entry replaces a bundled entry with the
+/// same prefix; new prefixes are added. The data applies to the whole process.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, is not valid JSON in this format, or
+/// contains invalid values. The data in use is then left unchanged.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::catalog::{context_window, load_model_data, price};
+///
+/// let path = std::env::temp_dir().join("turbine_model_data.json");
+/// std::fs::write(&path, r#"{"models": [
+///     {"prefix": "acme-large", "context_window": 64000, "input_per_million": 1.0, "output_per_million": 2.0}
+/// ]}"#)?;
+/// load_model_data(&path)?;
+/// assert_eq!(context_window("acme-large-2"), Some(64_000));
+/// assert_eq!(price("acme-large-2").unwrap().output_per_million, 2.0);
+///
+/// // A malformed file is rejected and the data stays as it was
+/// std::fs::write(&path, r#"{"models": [{"prefix": "acme-small", "context_window": 0}]}"#)?;
+/// assert!(load_model_data(&path).is_err());
+/// assert_eq!(context_window("acme-small"), None);
+/// # Ok::<(), turbine_llm::TurbineError>(())
+/// ```
+pub fn load_model_data(path: impl AsRef<Path>) -> Result<()> {
+    let data = ModelData::parse(&std::fs::read_to_string(path)?)?;
+    MODEL_DATA
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .merge(data);
+    Ok(())
+}
+
+/// Returns the context window of `model` in tokens, or `None` if the model is unknown.
+///
+/// Context windows come from the bundled model data and any loaded with
+/// [`load_model_data`].
+///
+/// A `provider/` prefix is ignored.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::catalog::context_window;
+///
+/// assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+/// assert_eq!(context_window("anthropic/claude-3-5-sonnet-20241022"), Some(200_000));
+/// assert_eq!(context_window("my-fine-tuned-model"), None);
+/// ```
+pub fn context_window(model: &str) -> Option<u32> {
+    MODEL_DATA
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .lookup(model, |entry| entry.context_window)
+}
+
+/// Commonly used models, for autocompletion and compile-time checked names.
+///
+/// Anything that takes a model name as `impl Into<String>`, such as [`LLMRequest::new`],
+/// also takes a `KnownModel`; models that are not listed are still passed as strings.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::{KnownModel, LLMRequest, Provider};
+///
+/// let request = LLMRequest::new(KnownModel::Gpt4oMini);
+/// assert_eq!(request.model, "gpt-4o-mini");
+///
+/// let request = LLMRequest::from(KnownModel::ClaudeSonnet4);
+/// assert_eq!(request.model, "claude-sonnet-4-20250514");
+/// assert_eq!(KnownModel::ClaudeSonnet4.provider(), Provider::Anthropic);
+///
+/// // Models that are not listed keep working as strings
+/// let request = LLMRequest::new("ft:gpt-4o-mini:acme::abc123");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownModel {
+    /// GPT-4o (`gpt-4o`)
+    Gpt4o,
+    /// GPT-4o mini (`gpt-4o-mini`)
+    Gpt4oMini,
+    /// GPT-4.1 (`gpt-4.1`)
+    Gpt41,
+    /// GPT-4.1 mini (`gpt-4.1-mini`)
+    Gpt41Mini,
+    /// GPT-4.1 nano (`gpt-4.1-nano`)
+    Gpt41Nano,
+    /// o3 (`o3`)
+    O3,
+    /// o3-mini (`o3-mini`)
+    O3Mini,
+    /// o4-mini (`o4-mini`)
+    O4Mini,
+    /// Claude 3.5 Haiku (`claude-3-5-haiku-20241022`)
+    Claude35Haiku,
+    /// Claude 3.5 Sonnet (`claude-3-5-sonnet-20241022`)
+    Claude35Sonnet,
+    /// Claude 3.7 Sonnet (`claude-3-7-sonnet-20250219`)
+    Claude37Sonnet,
+    /// Claude Sonnet 4 (`claude-sonnet-4-20250514`)
+    ClaudeSonnet4,
+    /// Claude Opus 4 (`claude-opus-4-20250514`)
+    ClaudeOpus4,
+    /// Gemini 2.0 Flash (`gemini-2.0-flash`)
+    Gemini20Flash,
+    /// Gemini 2.0 Flash-Lite (`gemini-2.0-flash-lite`)
+    Gemini20FlashLite,
+    /// Gemini 2.5 Flash (`gemini-2.5-flash`)
+    Gemini25Flash,
+    /// Gemini 2.5 Pro (`gemini-2.5-pro`)
+    Gemini25Pro,
+    /// Llama 3.1 8B Instant on Groq (`llama-3.1-8b-instant`)
+    Llama31_8bInstant,
+    /// Llama 3.3 70B Versatile on Groq (`llama-3.3-70b-versatile`)
+    Llama33_70bVersatile,
+}
+
+impl KnownModel {
+    /// All listed models.
+    pub const ALL: &'static [KnownModel] = &[
+        KnownModel::Gpt4o,
+        KnownModel::Gpt4oMini,
+        KnownModel::Gpt41,
+        KnownModel::Gpt41Mini,
+        KnownModel::Gpt41Nano,
+        KnownModel::O3,
+        KnownModel::O3Mini,
+        KnownModel::O4Mini,
+        KnownModel::Claude35Haiku,
+        KnownModel::Claude35Sonnet,
+        KnownModel::Claude37Sonnet,
+        KnownModel::ClaudeSonnet4,
+        KnownModel::ClaudeOpus4,
+        KnownModel::Gemini20Flash,
+        KnownModel::Gemini20FlashLite,
+        KnownModel::Gemini25Flash,
+        KnownModel::Gemini25Pro,
+        KnownModel::Llama31_8bInstant,
+        KnownModel::Llama33_70bVersatile,
+    ];
+
+    /// Returns the model name the provider's API expects.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KnownModel::Gpt4o => "gpt-4o",
+            KnownModel::Gpt4oMini => "gpt-4o-mini",
+            KnownModel::Gpt41 => "gpt-4.1",
+            KnownModel::Gpt41Mini => "gpt-4.1-mini",
+            KnownModel::Gpt41Nano => "gpt-4.1-nano",
+            KnownModel::O3 => "o3",
+            KnownModel::O3Mini => "o3-mini",
+            KnownModel::O4Mini => "o4-mini",
+            KnownModel::Claude35Haiku => "claude-3-5-haiku-20241022",
+            KnownModel::Claude35Sonnet => "claude-3-5-sonnet-20241022",
+            KnownModel::Claude37Sonnet => "claude-3-7-sonnet-20250219",
+            KnownModel::ClaudeSonnet4 => "claude-sonnet-4-20250514",
+            KnownModel::ClaudeOpus4 => "claude-opus-4-20250514",
+            KnownModel::Gemini20Flash => "gemini-2.0-flash",
+            KnownModel::Gemini20FlashLite => "gemini-2.0-flash-lite",
+            KnownModel::Gemini25Flash => "gemini-2.5-flash",
+            KnownModel::Gemini25Pro => "gemini-2.5-pro",
+            KnownModel::Llama31_8bInstant => "llama-3.1-8b-instant",
+            KnownModel::Llama33_70bVersatile => "llama-3.3-70b-versatile",
+        }
+    }
+
+    /// Returns the provider that serves the model.
+    pub fn provider(&self) -> Provider {
+        match self {
+            KnownModel::Gpt4o
+            | KnownModel::Gpt4oMini
+            | KnownModel::Gpt41
+            | KnownModel::Gpt41Mini
+            | KnownModel::Gpt41Nano
+            | KnownModel::O3
+            | KnownModel::O3Mini
+            | KnownModel::O4Mini => Provider::OpenAI,
+            KnownModel::Claude35Haiku
+            | KnownModel::Claude35Sonnet
+            | KnownModel::Claude37Sonnet
+            | KnownModel::ClaudeSonnet4
+            | KnownModel::ClaudeOpus4 => Provider::Anthropic,
+            KnownModel::Gemini20Flash
+            | KnownModel::Gemini20FlashLite
+            | KnownModel::Gemini25Flash
+            | KnownModel::Gemini25Pro => Provider::Gemini,
+            KnownModel::Llama31_8bInstant | KnownModel::Llama33_70bVersatile => Provider::Groq,
+        }
+    }
+}
+
+impl std::fmt::Display for KnownModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<KnownModel> for String {
+    fn from(model: KnownModel) -> Self {
+        model.as_str().to_string()
+    }
+}
+
+impl From<KnownModel> for LLMRequest {
+    fn from(model: KnownModel) -> Self {
+        LLMRequest::new(model)
+    }
+}
+
+/// Default model for each provider and [`Tier`].
+const TIER_MODELS: &[(Provider, Tier, &str)] = &[
+    (Provider::OpenAI, Tier::Fast, "gpt-4o-mini"),
+    (Provider::OpenAI, Tier::Balanced, "gpt-4o"),
+    (Provider::OpenAI, Tier::Powerful, "gpt-4.1"),
+    (Provider::Anthropic, Tier::Fast, "claude-3-5-haiku-20241022"),
+    (
+        Provider::Anthropic,
+        Tier::Balanced,
+        "claude-sonnet-4-20250514",
+    ),
+    (
+        Provider::Anthropic,
+        Tier::Powerful,
+        "claude-opus-4-20250514",
+    ),
+    (Provider::Gemini, Tier::Fast, "gemini-2.0-flash"),
+    (Provider::Gemini, Tier::Balanced, "gemini-2.5-flash"),
+    (Provider::Gemini, Tier::Powerful, "gemini-2.5-pro"),
+    (Provider::Groq, Tier::Fast, "llama-3.1-8b-instant"),
+    (Provider::Groq, Tier::Balanced, "llama-3.3-70b-versatile"),
+    (Provider::Groq, Tier::Powerful, "llama-3.3-70b-versatile"),
+];
+
+/// How capable a model a task needs, trading speed and cost for quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    /// Small, cheap and fast models for simple tasks
+    Fast,
+    /// General-purpose models
+    Balanced,
+    /// The most capable models, for complex reasoning
+    Powerful,
+}
+
+/// Maps each provider and [`Tier`] to a concrete model.
+///
+/// [`TierTable::default`] holds the built-in choices, which
+/// [`LLMRequest::for_task`](crate::LLMRequest::for_task) uses. Override entries to pin other
+/// models, e.g. from configuration.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::catalog::{Tier, TierTable};
+/// use turbine_llm::Provider;
+///
+/// let table = TierTable::default().with_model(Provider::OpenAI, Tier::Powerful, "o3");
+/// assert_eq!(table.model(Provider::OpenAI, Tier::Powerful), "o3");
+/// assert_eq!(table.model(Provider::OpenAI, Tier::Fast), "gpt-4o-mini");
+///
+/// let request = table.request(Provider::Gemini, Tier::Fast);
+/// assert_eq!(request.model, "gemini-2.0-flash");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierTable {
+    models: HashMap<(Provider, Tier), String>,
+}
+
+impl TierTable {
+    /// Uses `model` for `provider` and `tier`.
+    pub fn with_model(mut self, provider: Provider, tier: Tier, model: impl Into<String>) -> Self {
+        self.models.insert((provider, tier), model.into());
+        self
+    }
+
+    /// Returns the model for `provider` and `tier`.
+    pub fn model(&self, provider: Provider, tier: Tier) -> &str {
+        &self.models[&(provider, tier)]
+    }
+
+    /// Creates a request for the model of `provider` and `tier`.
+    pub fn request(&self, provider: Provider, tier: Tier) -> LLMRequest {
+        LLMRequest::new(self.model(provider, tier))
+    }
+}
+
+impl Default for TierTable {
+    fn default() -> Self {
+        Self {
+            models: TIER_MODELS
+                .iter()
+                .map(|(provider, tier, model)| ((*provider, *tier), model.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// The list price of a model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    /// US dollars per million input tokens
+    pub input_per_million: f64,
+    /// US dollars per million output tokens
+    pub output_per_million: f64,
+}
+
+/// Returns the list price of `model`, or `None` if the model is unknown.
+///
+/// Prices come from the bundled model data and any loaded with [`load_model_data`]. The
+/// bundled prices are the providers' standard rates at the time of release and ignore discounts
+/// such as cached input, batch processing or service tiers. A `provider/` prefix is
+/// ignored.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::catalog::price;
+///
+/// let gpt = price("gpt-4o-mini").unwrap();
+/// assert_eq!(gpt.input_per_million, 0.15);
+/// assert_eq!(gpt.output_per_million, 0.60);
+/// assert!(price("my-fine-tuned-model").is_none());
+/// ```
+pub fn price(model: &str) -> Option<ModelPrice> {
+    MODEL_DATA
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .lookup(model, |entry| {
+            Some(ModelPrice {
+                input_per_million: entry.input_per_million?,
+                output_per_million: entry.output_per_million?,
+            })
+        })
+}
@@ -1,13 +1,32 @@
 use crate::{
-    error::Result,
-    models::{LLMRequest, LLMResponse, Message},
+    audit::AuditLogger,
+    embeddings::{EmbeddingRequest, EmbeddingResponse},
+    error::{Result, TurbineError},
+    health::HealthStatus,
+    models::{LLMRequest, LLMResponse, Message, ResponseMetrics},
     providers::{
-        LLMProviderTrait, anthropic::AnthropicProvider, gemini::GeminiProvider, groq::GroqProvider,
-        openai::OpenAIProvider,
+        KeyProvider, LLMProviderTrait, NormalizedRequest, anthropic::AnthropicProvider,
+        gemini::GeminiProvider, groq::GroqProvider, normalize_request, openai::OpenAIProvider,
     },
-    types::Provider,
+    rate_limit::RateLimiter,
+    retry::RetryPolicy,
+    streaming::{
+        ChunkStream, JsonStream, RawEventStream, Reconnector, ResponseStream, StreamChunk,
+        split_thinking,
+    },
+    tokens::{self, Tokenizer},
+    transport::{DebugTransport, HttpTransport, ReqwestTransport},
+    types::{OutputFormat, Provider, Region, api_key_from_env_var},
 };
+use futures_util::{StreamExt, stream};
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
 use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 
 /// The main client for interacting with LLM providers.
 ///
@@ -34,12 +53,59 @@ use std::io::{self, Write};
 ///     Ok(())
 /// }
 /// ```
+///
+/// Cloning a client is cheap: clones share the provider and its connection pool, so a clone
+/// can be moved into each task that sends requests or consumes a stream.
+#[derive(Clone)]
 pub struct TurbineClient {
-    provider: Box<dyn LLMProviderTrait>,
+    provider: Arc<dyn LLMProviderTrait>,
     default_model: Option<String>,
+    retry_policy: RetryPolicy,
+    tokenizers: Vec<(String, Arc<dyn Tokenizer>)>,
+    response_transform: Option<ResponseTransform>,
+    input_guard: Option<Guard>,
+    output_guard: Option<Guard>,
+    default_output_format: Option<OutputFormat>,
+    default_max_tokens: Option<u32>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    stream_idle_timeout: Option<Duration>,
+    stream_reconnect: u32,
+    audit_logger: Option<Arc<AuditLogger>>,
+    model_validation: ModelValidation,
 }
 
+/// A function applied to the content of every response, see
+/// [`TurbineClient::with_response_transform`].
+pub type ResponseTransform = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+/// A policy check on text, see [`TurbineClient::with_input_guard`] and
+/// [`TurbineClient::with_output_guard`]. It returns the text to use, possibly changed, or
+/// the reason for rejecting it.
+pub type Guard = Arc<dyn Fn(String) -> std::result::Result<String, String> + Send + Sync>;
+
 impl TurbineClient {
+    pub(crate) fn from_parts(
+        provider: Box<dyn LLMProviderTrait>,
+        default_model: Option<String>,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            default_model,
+            retry_policy: RetryPolicy::none(),
+            tokenizers: Vec::new(),
+            response_transform: None,
+            input_guard: None,
+            output_guard: None,
+            default_output_format: None,
+            default_max_tokens: None,
+            rate_limiter: None,
+            stream_idle_timeout: None,
+            stream_reconnect: 0,
+            audit_logger: None,
+            model_validation: ModelValidation::Off,
+        }
+    }
+
     /// Creates a new client for the specified provider.
     ///
     /// The appropriate API key must be set as an environment variable before calling this.
@@ -65,10 +131,38 @@ impl TurbineClient {
             Provider::Groq => Box::new(GroqProvider::new()?),
         };
 
-        Ok(Self {
-            provider: provider_impl,
-            default_model: None,
-        })
+        Ok(Self::from_parts(provider_impl, None))
+    }
+
+    /// Creates a client for the only provider whose API key is set in the environment.
+    ///
+    /// See [`Provider::from_env`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no provider's key or keys for several providers are set.
+    ///
+    /// # Example
+    ///
+    /// ```standalone_crate
+    /// use turbine_llm::{Provider, TurbineClient};
+    ///
+    /// // SAFETY: no other thread reads the environment yet
+    /// unsafe {
+    ///     for name in ["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GEMINI_API_KEY", "GOOGLE_API_KEY"] {
+    ///         std::env::remove_var(name);
+    ///     }
+    ///     std::env::set_var("GROQ_API_KEY", "gsk-test");
+    /// }
+    /// let client = TurbineClient::from_env()?;
+    /// assert_eq!(client.provider_kind(), Some(Provider::Groq));
+    ///
+    /// unsafe { std::env::set_var("OPENAI_API_KEY", "sk-test") };
+    /// assert!(TurbineClient::from_env().is_err());
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        Self::new(Provider::from_env()?)
     }
 
     /// Creates a new client with an explicit API key.
@@ -92,10 +186,89 @@ impl TurbineClient {
             Provider::Groq => Box::new(GroqProvider::new_with_key(&api_key)),
         };
 
-        Self {
-            provider: provider_impl,
-            default_model: None,
-        }
+        Self::from_parts(provider_impl, None)
+    }
+
+    /// Creates a client around an already configured provider.
+    ///
+    /// Use this when a provider needs configuration that the other constructors don't
+    /// expose, such as [`GeminiProvider::new_vertex`] or a custom transport.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turbine_llm::TurbineClient;
+    /// use turbine_llm::providers::gemini::GeminiProvider;
+    ///
+    /// let provider = GeminiProvider::new_vertex("my-project", "us-central1", "ya29.token");
+    /// let client = TurbineClient::from_provider(provider);
+    /// ```
+    pub fn from_provider(provider: impl LLMProviderTrait + 'static) -> Self {
+        Self::from_parts(Box::new(provider), None)
+    }
+
+    /// Creates a client that fetches its API key from `key_provider` before every request.
+    ///
+    /// Use this for keys that come from a secrets manager or rotate, instead of reading a
+    /// fixed key at construction. An error returned by the callback fails the request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use turbine_llm::providers::{KeyProvider, LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, Provider, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let rotation = Arc::new(AtomicUsize::new(0));
+    /// let key_provider: KeyProvider = Arc::new(move || {
+    ///     // e.g. read the current key from Vault
+    ///     Ok(format!("sk-rotated-{}", rotation.fetch_add(1, Ordering::SeqCst)))
+    /// });
+    /// let client = TurbineClient::with_key_provider(Provider::OpenAI, key_provider.clone());
+    ///
+    /// // Each request asks the callback for a fresh key
+    /// let reply = r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#;
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(200, reply))
+    ///         .with_response(HttpResponse::new(200, reply)),
+    /// );
+    /// let provider = OpenAIProvider::new_with_key("unused")
+    ///     .with_key_provider(key_provider)
+    ///     .with_transport(mock.clone());
+    /// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Hello"));
+    /// provider.send_request(&request).await?;
+    /// provider.send_request(&request).await?;
+    ///
+    /// let keys: Vec<_> = mock.requests().iter().map(|r| r.header("authorization").unwrap().to_string()).collect();
+    /// assert_eq!(keys, ["Bearer sk-rotated-0", "Bearer sk-rotated-1"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_key_provider(provider: Provider, key_provider: KeyProvider) -> Self {
+        Self::from_parts(
+            provider_impl(
+                provider,
+                String::new(),
+                Some(key_provider),
+                Arc::new(ReqwestTransport::new()),
+                None,
+                None,
+                None,
+            ),
+            None,
+        )
+    }
+
+    /// Returns a builder for a client with custom HTTP settings.
+    ///
+    /// See [`TurbineClientBuilder`] for the available options.
+    pub fn builder(provider: Provider) -> TurbineClientBuilder {
+        TurbineClientBuilder::new(provider)
     }
 
     /// Creates a new client from a model string in format "provider/model-name".
@@ -126,7 +299,7 @@ impl TurbineClient {
 
         // Check if API key exists, prompt if not
         let env_var = provider.env_var();
-        if std::env::var(env_var).is_err() {
+        if provider.api_key_from_env().is_err() {
             println!("API key not found in environment.");
             println!("Provider: {:?}", provider);
             println!("Required environment variable: {}", env_var);
@@ -138,7 +311,7 @@ impl TurbineClient {
             let api_key = api_key.trim();
 
             if api_key.is_empty() {
-                return Err(crate::TurbineError::ApiKeyNotFound(env_var.to_string()));
+                return Err(TurbineError::ApiKeyNotFound(env_var.to_string()));
             }
 
             // Set the environment variable for this session
@@ -157,10 +330,7 @@ impl TurbineClient {
             Provider::Groq => Box::new(GroqProvider::new()?),
         };
 
-        Ok(Self {
-            provider: provider_impl,
-            default_model: Some(model_name),
-        })
+        Ok(Self::from_parts(provider_impl, Some(model_name)))
     }
 
     /// Creates a client from a model string with an explicit API key.
@@ -187,114 +357,2216 @@ impl TurbineClient {
             Provider::Groq => Box::new(GroqProvider::new_with_key(&api_key)),
         };
 
-        Ok(Self {
-            provider: provider_impl,
-            default_model: Some(model_name),
-        })
+        Ok(Self::from_parts(provider_impl, Some(model_name)))
     }
 
-    /// Sends a request to the LLM provider and returns the response.
-    ///
-    /// # Errors
+    /// Retries requests that fail with a retryable error, such as an overloaded provider.
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The API returns an error response
-    /// - The response cannot be parsed
+    /// Applies to [`send_request`](Self::send_request), [`embed`](Self::embed) and opening a
+    /// stream with [`send_request_stream`](Self::send_request_stream); errors in the middle
+    /// of a stream are not retried. Requests are not retried by default.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// # use turbine_llm::{TurbineClient, LLMRequest, Message, Provider};
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::retry::RetryPolicy;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient, TurbineError};
+    ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let client = TurbineClient::new(Provider::OpenAI)?;
-    /// let request = LLMRequest::new("gpt-4o-mini")
-    ///     .with_message(Message::user("Hello!"));
+    /// let overloaded = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(529, overloaded))
+    ///         .with_response(HttpResponse::new(
+    ///             200,
+    ///             r#"{"content":[{"type":"text","text":"Hi!"}],"usage":{"input_tokens":3,"output_tokens":2}}"#,
+    ///         )),
+    /// );
+    /// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+    /// let client = TurbineClient::from_provider(provider)
+    ///     .with_retry_policy(RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1)));
     ///
+    /// let request = LLMRequest::quick("claude-3-5-haiku-20241022", "Hello");
     /// let response = client.send_request(&request).await?;
-    /// println!("Response: {}", response.content);
+    /// assert_eq!(response.content, "Hi!");
+    /// assert_eq!(mock.requests().len(), 2);
+    ///
+    /// // Without retries, the overload is reported as such
+    /// mock.push_response(HttpResponse::new(529, overloaded));
+    /// let client = TurbineClient::from_provider(
+    ///     AnthropicProvider::new_with_key("sk-test").with_transport(mock),
+    /// );
+    /// let error = client.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::Overloaded(_)));
+    /// assert!(error.is_retryable());
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
-        self.provider.send_request(request).await
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
     }
 
-    /// Simplified method to send a single user message.
+    /// Applies `transform` to the content of every response before it is returned, e.g. to
+    /// strip `<think>` blocks or boilerplate in one place.
     ///
-    /// This is a convenience method for quick interactions. It uses the default model
-    /// set during `from_model()` construction.
+    /// The crate does not trim content or strip code fences itself, so `transform` sees the
+    /// provider's text as is. It runs on each response of
+    /// [`send_request`](Self::send_request) and the methods built on it, before JSON
+    /// validation and repair, so it can also clean up output that would not parse
+    /// otherwise. Streamed deltas are not transformed.
     ///
-    /// # Errors
+    /// # Example
     ///
-    /// Returns an error if:
-    /// - No default model was set (only works with `from_model()`)
-    /// - The HTTP request fails
-    /// - The API returns an error response
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"<think>easy</think>\n4"}}],"usage":{"prompt_tokens":5,"completion_tokens":6}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock))
+    ///     .with_response_transform(Arc::new(|content: String| match content.split_once("</think>") {
+    ///         Some((_, answer)) => answer.trim_start().to_string(),
+    ///         None => content,
+    ///     }));
+    ///
+    /// let response = client.send_request(&LLMRequest::quick("gpt-4o-mini", "2 + 2?")).await?;
+    /// assert_eq!(response.content, "4");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_response_transform(mut self, transform: ResponseTransform) -> Self {
+        self.response_transform = Some(transform);
+        self
+    }
+
+    /// Runs `guard` on the text of every user message before a request is sent, e.g. to
+    /// redact personal data or block content.
+    ///
+    /// The guard's text replaces the message text. A rejection fails the request with
+    /// [`TurbineError::Guardrail`] before anything is sent. It applies to
+    /// [`send_request`](Self::send_request), [`send_request_stream`](Self::send_request_stream)
+    /// and the methods built on them.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// # use turbine_llm::TurbineClient;
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::echo::EchoProvider;
+    /// use turbine_llm::{LLMRequest, TurbineClient, TurbineError};
+    ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let client = TurbineClient::from_model("openai/gpt-4o-mini")?;
+    /// let client = TurbineClient::from_provider(EchoProvider::new()).with_input_guard(Arc::new(|text: String| {
+    ///     if text.contains("password") {
+    ///         return Err("credentials are not allowed".to_string());
+    ///     }
+    ///     Ok(text.replace("alice@example.com", "[email]"))
+    /// }));
     ///
-    /// // Simple one-liner
-    /// let response = client.send("What is Rust?").await?;
-    /// println!("{}", response.content);
+    /// let response = client.send_request(&LLMRequest::quick("echo", "Mail alice@example.com")).await?;
+    /// assert_eq!(response.content, "Mail [email]");
+    ///
+    /// let error = client.send_request(&LLMRequest::quick("echo", "My password is hunter2")).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::Guardrail { reason } if reason == "credentials are not allowed"));
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send(&self, message: &str) -> Result<LLMResponse> {
-        let model = self.default_model.as_ref().ok_or_else(|| {
-            crate::TurbineError::MissingField(
-                "No default model set. Use from_model() constructor or send_request() directly"
-                    .to_string(),
-            )
-        })?;
+    pub fn with_input_guard(mut self, guard: Guard) -> Self {
+        self.input_guard = Some(guard);
+        self
+    }
 
-        let request = LLMRequest::new(model).with_message(Message::user(message));
+    /// Runs `guard` on the content of every response of
+    /// [`send_request`](Self::send_request) and the methods built on it, after the response
+    /// transform and JSON repair.
+    ///
+    /// The guard also runs on every alternative in `choices` and on the extracted
+    /// `reasoning`. The guard's text replaces the original. A rejection fails the call with
+    /// [`TurbineError::Guardrail`]; the tokens have still been billed. Streamed deltas are
+    /// not guarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::echo::EchoProvider;
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = TurbineClient::from_provider(EchoProvider::new())
+    ///     .with_output_guard(Arc::new(|text: String| Ok(text.replace("secret", "******"))));
+    ///
+    /// let response = client.send_request(&LLMRequest::quick("echo", "The secret is out")).await?;
+    /// assert_eq!(response.content, "The ****** is out");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// With several candidates, each one is guarded:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let body = r#"{"choices":[
+    ///     {"message":{"content":"No secrets here"},"finish_reason":"stop"},
+    ///     {"message":{"content":"The code is 1234"},"finish_reason":"stop"}],
+    ///     "usage":{"prompt_tokens":6,"completion_tokens":8}}"#;
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(200, body))
+    ///         .with_response(HttpResponse::new(200, body)),
+    /// );
+    /// let provider = || OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    /// let request = LLMRequest::quick("gpt-4o-mini", "Tell me something").with_candidate_count(2);
+    ///
+    /// let redacting = TurbineClient::from_provider(provider())
+    ///     .with_output_guard(Arc::new(|text: String| Ok(text.replace("1234", "****"))));
+    /// let response = redacting.send_request(&request).await?;
+    /// assert_eq!(response.choices.unwrap()[1].content, "The code is ****");
+    ///
+    /// let rejecting = TurbineClient::from_provider(provider()).with_output_guard(Arc::new(|text: String| {
+    ///     if text.contains("1234") { Err("leaked code".to_string()) } else { Ok(text) }
+    /// }));
+    /// let error = rejecting.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::Guardrail { .. }));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_output_guard(mut self, guard: Guard) -> Self {
+        self.output_guard = Some(guard);
+        self
+    }
 
-        self.send_request(&request).await
+    /// Sets the output format of requests whose
+    /// [`output_format`](LLMRequest::output_format) is `None`, i.e. that do not set one with
+    /// [`LLMRequest::with_output_format`] or the field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, OutputFormat, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reply = || {
+    ///     HttpResponse::new(
+    ///         200,
+    ///         r#"{"choices":[{"message":{"content":"{}"}}],"usage":{"prompt_tokens":5,"completion_tokens":1}}"#,
+    ///     )
+    /// };
+    /// let mock = Arc::new(MockTransport::new().with_response(reply()).with_response(reply()));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()))
+    ///     .with_default_output_format(OutputFormat::Json);
+    ///
+    /// client.send_request(&LLMRequest::quick("gpt-4o-mini", "Describe Paris in JSON")).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["response_format"]["type"], "json_object");
+    ///
+    /// // An explicit format wins
+    /// let request = LLMRequest::quick("gpt-4o-mini", "Hi").with_output_format(OutputFormat::Text);
+    /// client.send_request(&request).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert!(body.get("response_format").is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_default_output_format(mut self, format: OutputFormat) -> Self {
+        self.default_output_format = Some(format);
+        self
     }
 
-    /// Simplified method to send a message with a system prompt.
+    /// Sets `max_tokens` for requests whose [`max_tokens`](LLMRequest::max_tokens) is `None`,
+    /// instead of [`DEFAULT_MAX_TOKENS`](crate::DEFAULT_MAX_TOKENS), e.g. to standardize on a higher limit across a team.
     ///
-    /// Convenience method that combines system prompt and user message.
+    /// Requests that set their own value, with [`LLMRequest::with_max_tokens`] or the field,
+    /// keep it.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// # use turbine_llm::TurbineClient;
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let client = TurbineClient::from_model("anthropic/claude-3-5-sonnet")?;
+    /// let reply = || {
+    ///     HttpResponse::new(
+    ///         200,
+    ///         r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":5,"completion_tokens":1}}"#,
+    ///     )
+    /// };
+    /// let mock = Arc::new(MockTransport::new().with_response(reply()).with_response(reply()));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()))
+    ///     .with_default_max_tokens(4096);
     ///
-    /// let response = client.send_with_system(
-    ///     "You are a Rust expert",
-    ///     "Explain ownership in one sentence"
-    /// ).await?;
+    /// client.send_request(&LLMRequest::quick("gpt-4o-mini", "Hi")).await?;
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["max_tokens"], 4096);
+    ///
+    /// let mut request = LLMRequest::quick("gpt-4o-mini", "Hi");
+    /// request.max_tokens = Some(1024);
+    /// client.send_request(&request).await?;
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["max_tokens"], 1024);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send_with_system(
-        &self,
-        system_prompt: &str,
-        message: &str,
-    ) -> Result<LLMResponse> {
-        let model = self.default_model.as_ref().ok_or_else(|| {
-            crate::TurbineError::MissingField(
-                "No default model set. Use from_model() constructor or send_request() directly"
-                    .to_string(),
-            )
-        })?;
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.default_max_tokens = Some(max_tokens);
+        self
+    }
 
-        let request = LLMRequest::new(model)
-            .with_system_prompt(system_prompt)
-            .with_message(Message::user(message));
+    /// Delays requests so that this client, and its clones, stay below
+    /// `requests_per_minute` and `tokens_per_minute`. A limit of 0 disables it.
+    ///
+    /// A request counts its estimated input tokens plus its `max_tokens`. Requests that had
+    /// to wait report the delay in
+    /// [`ResponseMetrics::rate_limit_delay`](crate::ResponseMetrics::rate_limit_delay).
+    /// Retries of a failed request are not counted again. See [`RateLimiter`] for how the
+    /// limits are enforced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reply = || {
+    ///     HttpResponse::new(
+    ///         200,
+    ///         r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":5,"completion_tokens":1}}"#,
+    ///     )
+    /// };
+    /// let mock = Arc::new(MockTransport::new().with_response(reply()).with_response(reply()));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock))
+    ///     .with_rate_limit(500, 60_000);
+    ///
+    /// // The first request uses up the minute's tokens, the second waits for a refill
+    /// let first = client.send_request(&LLMRequest::quick("gpt-4o-mini", "Hi").with_max_tokens(60_000)).await?;
+    /// assert_eq!(first.metrics.unwrap().rate_limit_delay, None);
+    ///
+    /// let second = client.send_request(&LLMRequest::quick("gpt-4o-mini", "Hi").with_max_tokens(20)).await?;
+    /// assert!(second.metrics.unwrap().rate_limit_delay.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_rate_limit(mut self, requests_per_minute: u32, tokens_per_minute: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(
+            requests_per_minute,
+            tokens_per_minute,
+        )));
+        self
+    }
 
-        self.send_request(&request).await
+    /// Fails streams that go silent for longer than `limit` between two chunks, with
+    /// [`TurbineError::Timeout`] and reason `"idle"`.
+    ///
+    /// See [`ResponseStream::with_idle_timeout`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use turbine_llm::{Provider, TurbineClient};
+    ///
+    /// let client = TurbineClient::new_with_key(Provider::OpenAI, "sk-xxx")
+    ///     .with_stream_idle_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn with_stream_idle_timeout(mut self, limit: Duration) -> Self {
+        self.stream_idle_timeout = Some(limit);
+        self
+    }
+
+    /// Reconnects streams that fail mid-generation because the connection dropped or
+    /// with a retryable error (an idle timeout, an overloaded provider), up to
+    /// `max_attempts` times per stream.
+    ///
+    /// There is no provider-side resumption, so the request is sent again with the text
+    /// received so far as a partial reply to continue, as in
+    /// [`continue_message`](Self::continue_message), and the new deltas are appended to the
+    /// same stream. Each reconnect adds an entry to [`ResponseStream::warnings`]. The usage
+    /// reported afterwards is that of the last connection.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use futures_util::stream;
+    /// use std::io::{Error, ErrorKind};
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use turbine_llm::providers::LLMProviderTrait;
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::{LLMRequest, LLMResponse, Result, TurbineClient, TurbineError};
+    ///
+    /// /// Drops the first connection after a few words.
+    /// #[derive(Default)]
+    /// struct FlakyProvider {
+    ///     dropped: AtomicBool,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl LLMProviderTrait for FlakyProvider {
+    ///     async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+    ///         self.stream_request(request).await?.into_response().await
+    ///     }
+    ///
+    ///     async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+    ///         if !self.dropped.swap(true, Ordering::SeqCst) {
+    ///             let reset = Error::new(ErrorKind::ConnectionReset, "connection reset");
+    ///             return Ok(ResponseStream::from_stream(stream::iter([
+    ///                 Ok(StreamChunk::delta("Once upon")),
+    ///                 Err(TurbineError::IoError(reset)),
+    ///             ])));
+    ///         }
+    ///         // The partial reply is sent back to be continued
+    ///         assert_eq!(request.messages[1].content, "Once upon");
+    ///         Ok(ResponseStream::from_stream(stream::iter([Ok(StreamChunk::delta(" a time"))])))
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let client = TurbineClient::from_provider(FlakyProvider::default()).with_stream_reconnect(2);
+    ///
+    /// let stream = client.send_request_stream(&LLMRequest::quick("model", "Tell a story")).await?;
+    /// let response = stream.into_response().await?;
+    /// assert_eq!(response.content, "Once upon a time");
+    /// assert_eq!(response.warnings.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_stream_reconnect(mut self, max_attempts: u32) -> Self {
+        self.stream_reconnect = max_attempts;
+        self
+    }
+
+    /// Checks before sending that the requested model belongs to the client's provider.
+    ///
+    /// The model's provider is inferred with the rules of [`Provider::from_model_string`],
+    /// so `claude-3-5-sonnet` sent through an OpenAI client is caught before it turns into
+    /// a 404. Models the rules do not recognize (fine-tuned or custom names) and names
+    /// containing a `/` (such as Groq's `openai/gpt-oss-20b`) always pass, as do clients
+    /// without a built-in provider.
+    ///
+    /// With [`ModelValidation::Error`] a mismatch fails with [`TurbineError::InvalidRequest`];
+    /// with [`ModelValidation::Warn`] the request is sent and the mismatch is reported in
+    /// [`LLMResponse::warnings`] (streams are not warned about).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, ModelValidation, Provider, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = TurbineClient::new_with_key(Provider::OpenAI, "sk-xxx")
+    ///     .with_model_validation(ModelValidation::Error);
+    ///
+    /// let error = client
+    ///     .send_request(&LLMRequest::quick("claude-3-5-sonnet-latest", "Hi"))
+    ///     .await
+    ///     .unwrap_err();
+    /// assert!(matches!(error, TurbineError::InvalidRequest(message) if message.contains("Anthropic")));
+    /// # }
+    /// ```
+    pub fn with_model_validation(mut self, validation: ModelValidation) -> Self {
+        self.model_validation = validation;
+        self
+    }
+
+    /// Flushes the client's loggers before the application exits.
+    ///
+    /// Calling it is optional but recommended when an [`AuditLogger`] is attached: it
+    /// flushes the log and waits until it has reached the disk (see
+    /// [`AuditLogger::flush`]). The client starts no background tasks, so there is nothing
+    /// else to stop. Clones of the client keep working and share the flushed logger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be flushed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::audit::AuditLogger;
+    /// use turbine_llm::providers::echo::EchoProvider;
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path = std::env::temp_dir().join("turbine-shutdown-doctest.jsonl");
+    /// let client = TurbineClient::from_provider(EchoProvider::new())
+    ///     .with_audit_logger(AuditLogger::new(&path)?);
+    ///
+    /// client.send_request(&LLMRequest::quick("echo", "Bye")).await?;
+    /// client.shutdown().await?;
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(self) -> Result<()> {
+        match self.audit_logger {
+            Some(logger) => logger.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes every interaction completed through [`TurbineClient::send_request`],
+    /// [`TurbineClient::send_request_stream`] and the methods built on them to `logger`.
+    ///
+    /// Streams are logged once they have been read to the end, with the collected
+    /// response; streams that fail or are cancelled are not logged. A failed write does not
+    /// fail the request; it adds an entry to [`LLMResponse::warnings`] (or
+    /// [`ResponseStream::warnings`]) instead. Clones of the client share the logger, and
+    /// [`TurbineClient::shutdown`] flushes it.
+    ///
+    /// See [`AuditLogger`] for the format and an example.
+    pub fn with_audit_logger(mut self, logger: AuditLogger) -> Self {
+        self.audit_logger = Some(Arc::new(logger));
+        self
+    }
+
+    /// Counts tokens with `tokenizer` for models whose name starts with `model_prefix`
+    /// (e.g. `"gpt-4o"`).
+    ///
+    /// When several prefixes match a model, the longest wins. Models without a tokenizer
+    /// use the default of [`tokenizer`](Self::tokenizer).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::tokens::Tokenizer;
+    /// use turbine_llm::{LLMRequest, Provider, TurbineClient};
+    ///
+    /// /// Counts one token per byte, standing in for a real BPE tokenizer.
+    /// struct ByteTokenizer;
+    ///
+    /// impl Tokenizer for ByteTokenizer {
+    ///     fn count(&self, text: &str) -> usize {
+    ///         text.len()
+    ///     }
+    /// }
+    ///
+    /// let client = TurbineClient::new_with_key(Provider::OpenAI, "sk-test")
+    ///     .with_tokenizer("gpt-4o", ByteTokenizer);
+    ///
+    /// assert_eq!(client.count_tokens("gpt-4o-mini", "Hello, world!"), 13);
+    /// assert_eq!(client.count_tokens("gpt-3.5-turbo", "Hello, world!"), 4);
+    /// assert_eq!(client.estimate_input_tokens(&LLMRequest::quick("gpt-4o", "Hello")), 9);
+    /// ```
+    pub fn with_tokenizer(
+        mut self,
+        model_prefix: impl Into<String>,
+        tokenizer: impl Tokenizer + 'static,
+    ) -> Self {
+        self.tokenizers
+            .push((model_prefix.into(), Arc::new(tokenizer)));
+        self
+    }
+
+    /// Returns the tokenizer registered for `model`, or the default: the BPE tokenizer for
+    /// OpenAI models with the `tiktoken` feature, the heuristic otherwise.
+    pub fn tokenizer(&self, model: &str) -> &dyn Tokenizer {
+        self.tokenizers
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or_else(
+                || tokens::default_tokenizer(model),
+                |(_, tokenizer)| tokenizer.as_ref(),
+            )
+    }
+
+    /// Counts the tokens of `text` with the tokenizer for `model`.
+    pub fn count_tokens(&self, model: &str, text: &str) -> usize {
+        self.tokenizer(model).count(text)
+    }
+
+    /// Estimates the input tokens of `request` with the tokenizer for its model.
+    ///
+    /// See [`tokens::estimate_request_tokens_with`].
+    pub fn estimate_input_tokens(&self, request: &LLMRequest) -> u32 {
+        tokens::estimate_request_tokens_with(request, self.tokenizer(&request.model))
+    }
+
+    /// Returns which built-in provider this client uses, or `None` for a custom provider
+    /// passed to [`from_provider`](Self::from_provider).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{Provider, TurbineClient};
+    ///
+    /// let client = TurbineClient::from_model_with_key("anthropic/claude-3-5-sonnet-20241022", "sk-xxx")?;
+    /// assert_eq!(client.provider_kind(), Some(Provider::Anthropic));
+    /// assert_eq!(client.default_model(), Some("claude-3-5-sonnet-20241022"));
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn provider_kind(&self) -> Option<Provider> {
+        self.provider.provider_kind()
+    }
+
+    /// Returns the model used by [`send`](Self::send) and
+    /// [`send_with_system`](Self::send_with_system), if one was set.
+    pub fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+
+    /// Checks whether the provider's API is reachable and accepts the API key.
+    ///
+    /// The probe lists the provider's models, so it costs no tokens. Network failures are
+    /// reported as [`HealthStatus::Unreachable`] and rejected keys as
+    /// [`HealthStatus::AuthFailed`] rather than as errors, so the result can feed a
+    /// dashboard or a failover decision directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API key cannot be obtained, or
+    /// [`TurbineError::InvalidRequest`] for custom providers without a health check.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport, Method};
+    /// use turbine_llm::{HealthStatus, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(200, r#"{"data":[]}"#))
+    ///         .with_response(HttpResponse::new(401, r#"{"error":{"message":"invalid x-api-key"}}"#))
+    ///         .with_response(HttpResponse::new(503, "upstream unavailable")),
+    /// );
+    /// let client =
+    ///     TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone()));
+    ///
+    /// assert!(client.health_check().await?.is_healthy());
+    /// let probe = mock.last_request().unwrap();
+    /// assert_eq!(probe.method, Method::Get);
+    /// assert_eq!(probe.url, "https://api.anthropic.com/v1/models");
+    ///
+    /// assert!(matches!(client.health_check().await?, HealthStatus::AuthFailed { .. }));
+    /// assert!(matches!(
+    ///     client.health_check().await?,
+    ///     HealthStatus::Unavailable { status: 503, .. }
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn health_check(&self) -> Result<HealthStatus> {
+        self.provider.health_check().await
+    }
+
+    /// Sends a request to the LLM provider and returns the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The API returns an error response
+    /// - The response cannot be parsed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use turbine_llm::{TurbineClient, LLMRequest, Message, Provider};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = TurbineClient::new(Provider::OpenAI)?;
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_message(Message::user("Hello!"));
+    ///
+    /// let response = client.send_request(&request).await?;
+    /// println!("Response: {}", response.content);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let request = self.guard_input(self.with_defaults(request))?;
+        let mismatch = self.check_model(&request)?;
+        self.send_prepared(&request, mismatch).await
+    }
+
+    /// Sends a request that already has the defaults, input guard and model validation
+    /// applied, then runs the output guard and writes the audit log.
+    async fn send_prepared(
+        &self,
+        request: &LLMRequest,
+        mismatch: Option<String>,
+    ) -> Result<LLMResponse> {
+        let mut response = self.complete(request).await?;
+        response.warnings.extend(mismatch);
+        self.guard_output(&mut response)?;
+
+        if let Some(logger) = &self.audit_logger
+            && let Err(error) = logger.log(request, &response)
+        {
+            response
+                .warnings
+                .push(format!("Could not write the audit log: {}", error));
+        }
+        Ok(response)
+    }
+
+    /// Runs the output guard, if any, on the content, every choice and the reasoning of
+    /// `response`.
+    fn guard_output(&self, response: &mut LLMResponse) -> Result<()> {
+        let Some(guard) = &self.output_guard else {
+            return Ok(());
+        };
+        let run = |text: &mut String| -> Result<()> {
+            *text =
+                guard(std::mem::take(text)).map_err(|reason| TurbineError::Guardrail { reason })?;
+            Ok(())
+        };
+
+        run(&mut response.content)?;
+        for choice in response.choices.iter_mut().flatten() {
+            run(&mut choice.content)?;
+        }
+        if let Some(reasoning) = &mut response.reasoning {
+            run(reasoning)?;
+        }
+        Ok(())
+    }
+
+    /// Sends `request` and runs the JSON validation and repair it asks for.
+    async fn complete(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let mut response = self.send_once(request).await?;
+
+        let validate = request.json_validation || request.json_repair_attempts > 0;
+        if !validate || !request.effective_output_format().is_json() {
+            return Ok(response);
+        }
+
+        let mut follow_up = request.clone();
+        let mut repairs = 0;
+        for _ in 0..request.json_repair_attempts {
+            let Some(error) = json_error(&response) else {
+                break;
+            };
+
+            follow_up
+                .messages
+                .push(Message::assistant(response.content.clone()));
+            follow_up.messages.push(Message::user(format!(
+                "Your last response was invalid JSON: {}. Return only valid JSON.",
+                error
+            )));
+
+            let usage = response.usage;
+            response = self.send_once(&follow_up).await?;
+            response.usage += &usage;
+            repairs += 1;
+        }
+        if repairs > 0 {
+            response.warnings.push(format!(
+                "Response was not valid JSON; sent {} repair request(s)",
+                repairs
+            ));
+        }
+
+        validate_json(&response)?;
+        Ok(response)
+    }
+
+    /// Runs the input guard, if any, on the user messages of `request`.
+    fn guard_input<'a>(&self, mut request: Cow<'a, LLMRequest>) -> Result<Cow<'a, LLMRequest>> {
+        let Some(guard) = &self.input_guard else {
+            return Ok(request);
+        };
+        for message in request.to_mut().messages.iter_mut() {
+            if message.role == "user" {
+                message.content = guard(std::mem::take(&mut message.content))
+                    .map_err(|reason| TurbineError::Guardrail { reason })?;
+            }
+        }
+        Ok(request)
+    }
+
+    /// Returns how a stream of `request` reopens its connection after a drop.
+    fn reconnector(&self, request: &LLMRequest) -> Reconnector {
+        let provider = self.provider.clone();
+        let request = request.clone();
+        let anthropic = self.provider_kind() == Some(Provider::Anthropic);
+        Box::new(move |partial: String| {
+            let provider = provider.clone();
+            let mut request = request.clone();
+            if !partial.is_empty() {
+                request.messages = continuation(&request.messages, &partial, anthropic);
+            }
+            Box::pin(async move {
+                let stream = provider.stream_request(&request).await?;
+                Ok(Box::pin(stream) as ChunkStream)
+            })
+        })
+    }
+
+    /// Applies the model validation: returns the mismatch as an error or, when only warning,
+    /// as a message for [`LLMResponse::warnings`].
+    fn check_model(&self, request: &LLMRequest) -> Result<Option<String>> {
+        if self.model_validation == ModelValidation::Off || request.model.contains('/') {
+            return Ok(None);
+        }
+        let (Some(provider), Ok((inferred, _))) = (
+            self.provider_kind(),
+            Provider::from_model_string(&request.model),
+        ) else {
+            return Ok(None);
+        };
+        if inferred == provider {
+            return Ok(None);
+        }
+
+        let message = format!(
+            "Model `{}` looks like a {:?} model, but this client uses {:?}; \
+             use a {:?} client or TurbineClient::from_model",
+            request.model, inferred, provider, inferred
+        );
+        match self.model_validation {
+            ModelValidation::Error => Err(TurbineError::InvalidRequest(message)),
+            _ => Ok(Some(message)),
+        }
+    }
+
+    /// Waits for the rate limit, if any, and returns how long the request was delayed.
+    async fn throttle(&self, request: &LLMRequest) -> Option<Duration> {
+        let limiter = self.rate_limiter.as_ref()?;
+        let tokens = self
+            .estimate_input_tokens(request)
+            .saturating_add(request.effective_max_tokens());
+        let waited = limiter
+            .acquire_with_priority(tokens, request.priority)
+            .await;
+        (!waited.is_zero()).then_some(waited)
+    }
+
+    /// Returns `request` with the client's defaults applied.
+    fn with_defaults<'a>(&self, request: &'a LLMRequest) -> Cow<'a, LLMRequest> {
+        let mut request = Cow::Borrowed(request);
+        if request.output_format.is_none() && self.default_output_format.is_some() {
+            request.to_mut().output_format = self.default_output_format.clone();
+        }
+        if request.max_tokens.is_none() && self.default_max_tokens.is_some() {
+            request.to_mut().max_tokens = self.default_max_tokens;
+        }
+        request
+    }
+
+    /// Sends a single request with retries, extracts reasoning if requested and applies the
+    /// response transform.
+    async fn send_once(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let delay = self.throttle(request).await;
+        let (mut response, duration) = self
+            .retry_policy
+            .run(|| async {
+                let started = Instant::now();
+                let response = self.provider.send_request(request).await?;
+                Ok((response, started.elapsed()))
+            })
+            .await?;
+        let metrics = response
+            .metrics
+            .get_or_insert_with(ResponseMetrics::default);
+        metrics.duration = Some(duration);
+        metrics.rate_limit_delay = delay;
+        metrics.tokens_per_second =
+            ResponseMetrics::throughput(response.usage.output_tokens, duration);
+        if request.extract_thinking {
+            let (reasoning, answer) = split_thinking(&response.content);
+            response.content = answer;
+            if reasoning.is_some() {
+                response.reasoning = reasoning;
+            }
+        }
+        if let Some(transform) = &self.response_transform {
+            response.content = transform(std::mem::take(&mut response.content));
+        }
+        Ok(response)
+    }
+
+    /// Like [`send_request`](Self::send_request), but also returns the effective request
+    /// the provider sent.
+    ///
+    /// The [`NormalizedRequest`] has the provider's role mapping, merged system prompt,
+    /// JSON instruction and filled-in defaults such as Anthropic's `max_tokens`, so it can
+    /// be logged to reproduce a call or to explain why two similar calls behaved
+    /// differently. It reflects the request after the client's defaults and input guard,
+    /// so redacted text is reported as sent. Custom providers get the request unchanged.
+    /// JSON repair follow-ups are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"Bonjour"}],"usage":{"input_tokens":9,"output_tokens":2}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let request = LLMRequest::new("claude-3-5-haiku-20241022")
+    ///     .with_system_prompt("Be brief.")
+    ///     .with_message(Message::system("Answer in French."))
+    ///     .with_message(Message::user("Hello"));
+    ///
+    /// let (response, normalized) = client.send_request_normalized(&request).await?;
+    /// assert_eq!(response.content, "Bonjour");
+    /// assert_eq!(normalized.system.as_deref(), Some("Be brief.\n\nAnswer in French."));
+    /// assert_eq!(normalized.max_tokens, Some(1024));
+    /// assert_eq!(normalized.messages.len(), 1);
+    ///
+    /// // Ready for a reproducibility log
+    /// let logged = serde_json::to_value(&normalized)?;
+    /// assert_eq!(logged["provider"], "anthropic");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// With an input guard, the normalized request holds the guarded text:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::echo::EchoProvider;
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = TurbineClient::from_provider(EchoProvider::new())
+    ///     .with_input_guard(Arc::new(|text: String| Ok(text.replace("alice@example.com", "[email]"))));
+    ///
+    /// let request = LLMRequest::quick("echo", "Mail alice@example.com");
+    /// let (response, normalized) = client.send_request_normalized(&request).await?;
+    /// assert_eq!(response.content, "Mail [email]");
+    /// assert_eq!(normalized.messages[0].content, "Mail [email]");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request_normalized(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<(LLMResponse, NormalizedRequest)> {
+        let request = self.guard_input(self.with_defaults(request))?;
+        let mismatch = self.check_model(&request)?;
+        let normalized = match self.provider_kind() {
+            Some(provider) => normalize_request(provider, &request),
+            None => NormalizedRequest {
+                provider: None,
+                model: request.model.clone(),
+                system: request.system_prompt.clone(),
+                messages: request.messages.clone(),
+                max_tokens: Some(request.effective_max_tokens()),
+                max_completion_tokens: request.max_completion_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                output_format: request.effective_output_format().clone(),
+                tools: request.tools.clone(),
+                candidate_count: request.candidate_count,
+                service_tier: request.service_tier.clone(),
+                anthropic_user_id: request.anthropic_user_id.clone(),
+            },
+        };
+        let response = self.send_prepared(&request, mismatch).await?;
+        Ok((response, normalized))
+    }
+
+    /// Like [`send_request`](Self::send_request), but aborts when `cancel` is cancelled.
+    ///
+    /// Returns [`TurbineError::Cancelled`] if the token is cancelled before or while the
+    /// request is in flight; the HTTP request is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use turbine_llm::{CancellationToken, TurbineClient, LLMRequest, Message, Provider, TurbineError};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = TurbineClient::new(Provider::OpenAI)?;
+    /// let cancel = CancellationToken::new();
+    /// let on_shutdown = cancel.clone();
+    /// tokio::spawn(async move {
+    ///     tokio::signal::ctrl_c().await.ok();
+    ///     on_shutdown.cancel();
+    /// });
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Write an essay"));
+    /// match client.send_request_cancellable(&request, &cancel).await {
+    ///     Err(TurbineError::Cancelled) => println!("Stopped by user"),
+    ///     result => println!("{}", result?.content),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request_cancellable(
+        &self,
+        request: &LLMRequest,
+        cancel: &CancellationToken,
+    ) -> Result<LLMResponse> {
+        if cancel.is_cancelled() {
+            return Err(TurbineError::Cancelled);
+        }
+        tokio::select! {
+            _ = cancel.cancelled() => Err(TurbineError::Cancelled),
+            result = self.send_request(request) => result,
+        }
+    }
+
+    /// Embeds the texts of `request`.
+    ///
+    /// See [`crate::embeddings`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::InvalidRequest`] if the provider has no embeddings API or
+    /// `dimensions` is outside the model's supported range, and an error if the HTTP request
+    /// fails or the API returns an error response.
+    pub async fn embed(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(0).await;
+        }
+        self.retry_policy.run(|| self.provider.embed(request)).await
+    }
+
+    /// Classifies `text` into exactly one of `labels`.
+    ///
+    /// The model is asked to answer with a label only, at temperature 0. The answer is
+    /// matched against `labels` ignoring case, surrounding whitespace, quotes and trailing
+    /// punctuation, and the matching label is returned as given. If the answer matches no
+    /// label, the model is asked again, up to two more times.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::MissingField`] if `labels` is empty, and
+    /// [`TurbineError::InvalidResponse`] if the model never answers with a valid label.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::TurbineClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reply = |label: &str| {
+    ///     HttpResponse::new(200, format!(
+    ///         r#"{{"choices":[{{"message":{{"content":"{}"}}}}],"usage":{{"prompt_tokens":30,"completion_tokens":2}}}}"#,
+    ///         label
+    ///     ))
+    /// };
+    /// let mock = Arc::new(MockTransport::new().with_response(reply("Maybe")).with_response(reply("Positive.")));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let label = client
+    ///     .classify("gpt-4o-mini", "I love this crate!", &["positive", "negative", "neutral"])
+    ///     .await?;
+    /// assert_eq!(label, "positive");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn classify(&self, model: &str, text: &str, labels: &[&str]) -> Result<String> {
+        const MAX_ATTEMPTS: usize = 3;
+
+        if labels.is_empty() {
+            return Err(TurbineError::MissingField(
+                "At least one label is required".to_string(),
+            ));
+        }
+
+        let label_list = labels.join(", ");
+        let mut request = LLMRequest::new(model)
+            .with_system_prompt(format!(
+                "Classify the user's text into exactly one of these labels: {}. \
+                 Respond with the label only, without any other text.",
+                label_list
+            ))
+            .with_temperature(0.0)
+            .with_max_tokens(64)
+            .with_message(Message::user(text));
+
+        let mut answer = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            answer = self.send_request(&request).await?.content;
+            let normalized = answer
+                .trim()
+                .trim_matches(|c: char| c == '"' || c == '\'' || c == '`')
+                .trim_end_matches(['.', '!'])
+                .trim();
+            if let Some(label) = labels.iter().find(|l| l.eq_ignore_ascii_case(normalized)) {
+                return Ok(label.to_string());
+            }
+
+            request.messages.push(Message::assistant(answer.clone()));
+            request.messages.push(Message::user(format!(
+                "\"{}\" is not a valid label. Respond with exactly one of: {}",
+                answer.trim(),
+                label_list
+            )));
+        }
+
+        Err(TurbineError::InvalidResponse(format!(
+            "Model answered \"{}\", which is not one of the labels: {}",
+            answer.trim(),
+            label_list
+        )))
+    }
+
+    /// Sends several requests concurrently and returns their results in order.
+    ///
+    /// At most `concurrency` requests are in flight at once (at least one). A failed request
+    /// does not stop the others.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use turbine_llm::{TurbineClient, LLMRequest, Message, Provider};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = TurbineClient::new(Provider::OpenAI)?;
+    /// let requests: Vec<LLMRequest> = ["Rust", "Go", "Zig"]
+    ///     .iter()
+    ///     .map(|lang| LLMRequest::new("gpt-4o-mini").with_message(Message::user(format!("Describe {}", lang))))
+    ///     .collect();
+    ///
+    /// for result in client.send_batch(&requests, 2).await {
+    ///     println!("{}", result?.content);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_batch(
+        &self,
+        requests: &[LLMRequest],
+        concurrency: usize,
+    ) -> Vec<Result<LLMResponse>> {
+        self.send_batch_cancellable(requests, concurrency, &CancellationToken::new())
+            .await
+    }
+
+    /// Like [`send_batch`](Self::send_batch), but stops when `cancel` is cancelled.
+    ///
+    /// Once the token is cancelled, no new requests are started and in-flight requests are
+    /// aborted. Both report [`TurbineError::Cancelled`]; requests that already completed keep
+    /// their results. Share clones of the token to cancel several batches at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{CancellationToken, LLMRequest, Message, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()));
+    /// let requests = vec![LLMRequest::new("gpt-4o-mini").with_message(Message::user("Hello")); 3];
+    ///
+    /// let cancel = CancellationToken::new();
+    /// cancel.cancel();
+    /// let results = client.send_batch_cancellable(&requests, 2, &cancel).await;
+    ///
+    /// assert!(results.iter().all(|r| matches!(r, Err(TurbineError::Cancelled))));
+    /// assert!(mock.requests().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_batch_cancellable(
+        &self,
+        requests: &[LLMRequest],
+        concurrency: usize,
+        cancel: &CancellationToken,
+    ) -> Vec<Result<LLMResponse>> {
+        stream::iter(requests)
+            .map(|request| self.send_request_cancellable(request, cancel))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Sends a request and streams the response as it is generated.
+    ///
+    /// The returned [`ResponseStream`] yields text deltas and ends with a chunk carrying the
+    /// total usage and finish reason. Call [`ResponseStream::into_response`] at any point to
+    /// collect the remaining deltas into a regular [`LLMResponse`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the API returns an error response.
+    /// Errors that occur mid-stream are yielded by the stream itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{FinishReason, LLMRequest, Message, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "data: {\"model\":\"gpt-4o-mini-2024-07-18\",\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+    ///     "data: {\"model\":\"gpt-4o-mini-2024-07-18\",\"choices\":[{\"delta\":{\"content\":\"lo!\"},\"finish_reason\":\"stop\"}]}\n\n",
+    ///     "data: {\"model\":\"gpt-4o-mini-2024-07-18\",\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":2}}\n\n",
+    ///     "data: [DONE]\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client = TurbineClient::from_provider(
+    ///     OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()),
+    /// );
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Hello"));
+    /// let mut stream = client.send_request_stream(&request).await?;
+    ///
+    /// let first = stream.next().await.unwrap()?;
+    /// assert_eq!(first.delta, "Hel");
+    ///
+    /// let response = stream.into_response().await?;
+    /// assert_eq!(response.content, "Hello!");
+    /// assert_eq!(response.usage.output_tokens, 2);
+    /// assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+    /// assert_eq!(response.model, "gpt-4o-mini-2024-07-18");
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["stream"], true);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_request_stream(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let request = self.guard_input(self.with_defaults(request))?;
+        let request = &*request;
+        self.check_model(request)?;
+        let delay = self.throttle(request).await;
+        let started = Instant::now();
+        let stream = self
+            .retry_policy
+            .run(|| self.provider.stream_request(request))
+            .await?;
+        let mut stream = stream.with_started_at(started).with_rate_limit_delay(delay);
+        if let Some(limit) = self.stream_idle_timeout {
+            stream = stream.with_idle_timeout(limit);
+        }
+        if self.stream_reconnect > 0 {
+            stream = stream.with_reconnect(self.stream_reconnect, self.reconnector(request));
+        }
+        if let Some(logger) = &self.audit_logger {
+            stream = stream.with_audit_logger(logger.clone(), request.clone());
+        }
+        Ok(if request.extract_thinking {
+            stream.with_extract_thinking()
+        } else {
+            stream
+        })
+    }
+
+    /// Sends a request with streaming and returns the provider's server-sent events as
+    /// they arrive, without turning them into text deltas.
+    ///
+    /// This is the layer [`send_request_stream`](Self::send_request_stream) parses, for
+    /// diagnosing streaming quirks or for consumers that need message boundaries. Each
+    /// [`RawEvent`](crate::RawEvent) keeps the data as sent; [`parse`](crate::RawEvent::parse)
+    /// turns it into a [`ProviderEvent`](crate::ProviderEvent). The client's defaults, input
+    /// guard, model validation, rate limit and retry policy apply; the stream-only settings
+    /// (idle timeout, reconnect, thinking extraction) do not.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::InvalidRequest`] for custom providers that do not implement
+    /// [`LLMProviderTrait::stream_raw_events`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::StreamExt;
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Provider, ProviderEvent, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "event: message_start\n",
+    ///     "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":5,\"output_tokens\":1}}}\n\n",
+    ///     "event: content_block_delta\n",
+    ///     "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n",
+    ///     "event: message_stop\n",
+    ///     "data: {\"type\":\"message_stop\"}\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client = TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let events: Vec<_> = client
+    ///     .stream_raw_events(&LLMRequest::quick("claude-3-5-haiku-latest", "Hello"))
+    ///     .await?
+    ///     .collect()
+    ///     .await;
+    /// let types: Vec<_> = events.iter().map(|event| event.as_ref().unwrap().event.as_deref()).collect();
+    /// assert_eq!(types, [Some("message_start"), Some("content_block_delta"), Some("message_stop")]);
+    ///
+    /// let delta = events[1].as_ref().unwrap();
+    /// assert_eq!(delta.provider, Provider::Anthropic);
+    /// assert_eq!(delta.json()?["delta"]["text"], "Hi");
+    /// assert!(matches!(
+    ///     delta.parse(),
+    ///     ProviderEvent::Anthropic { event_type, .. } if event_type == "content_block_delta"
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_raw_events(&self, request: &LLMRequest) -> Result<RawEventStream> {
+        let request = self.guard_input(self.with_defaults(request))?;
+        let request = &*request;
+        self.check_model(request)?;
+        self.throttle(request).await;
+        self.retry_policy
+            .run(|| self.provider.stream_raw_events(request))
+            .await
+    }
+
+    /// Opens a stream for each request, concurrently, and returns them in the order of
+    /// `requests`.
+    ///
+    /// The streams are independent of each other and of the client: each can be consumed at
+    /// its own pace, e.g. in a separate task, and a failing request only affects its own
+    /// entry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use futures_util::StreamExt;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = |text: &str| {
+    ///     format!(
+    ///         "data: {{\"choices\":[{{\"delta\":{{\"content\":\"{text}\"}},\"finish_reason\":\"stop\"}}]}}\n\ndata: [DONE]\n\n"
+    ///     )
+    /// };
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(200, sse("Paris")))
+    ///         .with_response(HttpResponse::new(200, sse("Paris"))),
+    /// );
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let requests = vec![
+    ///     LLMRequest::quick("gpt-4o-mini", "Capital of France?"),
+    ///     LLMRequest::quick("gpt-4o", "Capital of France?"),
+    /// ];
+    /// let mut tasks = Vec::new();
+    /// for stream in client.stream_batch(&requests).await {
+    ///     let mut stream = stream?;
+    ///     tasks.push(tokio::spawn(async move {
+    ///         let mut text = String::new();
+    ///         while let Some(chunk) = stream.next().await {
+    ///             text.push_str(&chunk?.delta);
+    ///         }
+    ///         Ok::<_, turbine_llm::TurbineError>(text)
+    ///     }));
+    /// }
+    /// for task in tasks {
+    ///     assert_eq!(task.await??, "Paris");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_batch(&self, requests: &[LLMRequest]) -> Vec<Result<ResponseStream>> {
+        stream::iter(requests)
+            .map(|request| self.send_request_stream(request))
+            .buffered(requests.len().max(1))
+            .collect()
+            .await
+    }
+
+    /// Streams the response to `on_token` and returns the complete response at the end.
+    ///
+    /// `on_token` is called with every non-empty text delta as it arrives, e.g. to display
+    /// it; the returned [`LLMResponse`] holds the full content, usage and finish reason, e.g.
+    /// to store it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the stream yields an error; `on_token` may
+    /// already have been called for part of the response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "event: message_start\n",
+    ///     "data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-3-5-haiku-20241022\",\"usage\":{\"input_tokens\":5,\"output_tokens\":0}}}\n\n",
+    ///     "event: content_block_delta\n",
+    ///     "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Hel\"}}\n\n",
+    ///     "event: content_block_delta\n",
+    ///     "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"lo!\"}}\n\n",
+    ///     "event: message_delta\n",
+    ///     "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":2}}\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client =
+    ///     TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let mut shown = Vec::new();
+    /// let request = LLMRequest::quick("claude-3-5-haiku-20241022", "Hello");
+    /// let response = client
+    ///     .send_streaming_collect(&request, |token| shown.push(token.to_string()))
+    ///     .await?;
+    ///
+    /// assert_eq!(shown, ["Hel", "lo!"]);
+    /// assert_eq!(response.content, "Hello!");
+    /// assert_eq!(response.usage.input_tokens, 5);
+    /// assert_eq!(response.usage.output_tokens, 2);
+    /// assert_eq!(response.model, "claude-3-5-haiku-20241022");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_streaming_collect<F>(
+        &self,
+        request: &LLMRequest,
+        mut on_token: F,
+    ) -> Result<LLMResponse>
+    where
+        F: FnMut(&str),
+    {
+        let mut stream = self.send_request_stream(request).await?;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            if !chunk.delta.is_empty() {
+                on_token(&chunk.delta);
+            }
+        }
+        stream.into_response().await
+    }
+
+    /// Streams the response to `writer` as newline-delimited JSON, one
+    /// [`StreamChunk`] per line.
+    ///
+    /// Each text delta is written as `{"delta":"...","done":false}` and flushed as it
+    /// arrives; the last line is the terminal chunk with `"done":true`, the usage and the
+    /// finish reason. This makes it easy to pipe a response into tools that read JSONL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the stream yields an error, or writing fails.
+    /// Lines written before the error are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n",
+    ///     "data: {\"choices\":[{\"delta\":{\"content\":\"!\"},\"finish_reason\":\"stop\"}]}\n\n",
+    ///     "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":2}}\n\n",
+    ///     "data: [DONE]\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client =
+    ///     TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// // Use tokio::io::stdout() to feed a shell pipeline
+    /// let mut output = Vec::new();
+    /// client
+    ///     .stream_ndjson_to_writer(&LLMRequest::quick("gpt-4o-mini", "Hello"), &mut output)
+    ///     .await?;
+    ///
+    /// let lines: Vec<&str> = std::str::from_utf8(&output)?.lines().collect();
+    /// assert_eq!(lines[0], r#"{"delta":"Hi","done":false}"#);
+    /// assert_eq!(lines[1], r#"{"delta":"!","done":false,"finish_reason":"stop"}"#);
+    /// assert_eq!(
+    ///     lines[2],
+    ///     r#"{"delta":"","done":true,"usage":{"input_tokens":5,"output_tokens":2},"finish_reason":"stop"}"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_ndjson_to_writer<W>(
+        &self,
+        request: &LLMRequest,
+        mut writer: W,
+    ) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut stream = self.send_request_stream(request).await?;
+        while let Some(chunk) = stream.next().await {
+            let mut line = serde_json::to_vec(&chunk?)?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Streams the response into a broadcast channel so that several subscribers receive the
+    /// same tokens, and returns the assembled response.
+    ///
+    /// Every text delta is sent as a [`StreamChunk`], followed by the
+    /// terminal chunk with `done` set. Subscribers only receive chunks sent after they
+    /// subscribed, so a late subscriber misses the earlier tokens; subscribers that fall
+    /// behind the channel's capacity get
+    /// [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged). Sending
+    /// while nobody is subscribed is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the stream yields an error. Subscribers then
+    /// receive no terminal chunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tokio::sync::broadcast;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+    ///     "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+    ///     "data: [DONE]\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client =
+    ///     TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let (sender, mut first) = broadcast::channel(16);
+    /// let mut second = sender.subscribe();
+    /// let response = client
+    ///     .stream_to_broadcast(&LLMRequest::quick("gpt-4o-mini", "Hello"), &sender)
+    ///     .await?;
+    /// assert_eq!(response.content, "Hello");
+    ///
+    /// for receiver in [&mut first, &mut second] {
+    ///     assert_eq!(receiver.recv().await?.delta, "Hel");
+    ///     assert_eq!(receiver.recv().await?.delta, "lo");
+    ///     assert!(receiver.recv().await?.done);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_to_broadcast(
+        &self,
+        request: &LLMRequest,
+        sender: &broadcast::Sender<StreamChunk>,
+    ) -> Result<LLMResponse> {
+        let mut stream = self.send_request_stream(request).await?;
+        while let Some(chunk) = stream.next().await {
+            // Fails only when nobody is subscribed, which is fine
+            let _ = sender.send(chunk?);
+        }
+        stream.into_response().await
+    }
+
+    /// Sends a request and streams the response as parsed JSON values.
+    ///
+    /// For a top-level JSON array, each element is yielded as soon as it is complete;
+    /// otherwise the whole value is yielded once complete. See [`JsonStream`]. Consider
+    /// setting [`OutputFormat::Json`] on the request; note that
+    /// OpenAI's JSON mode requires a top-level object, so wrap arrays in an object there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or the API returns an error response.
+    /// Values that fail to deserialize into `T` are yielded as errors by the stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let sse = concat!(
+    ///     "event: content_block_delta\n",
+    ///     "data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"[\\\"red\\\", \\\"gr\"}}\n\n",
+    ///     "event: content_block_delta\n",
+    ///     "data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"een\\\"]\"}}\n\n",
+    /// );
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+    /// let client = TurbineClient::from_provider(
+    ///     AnthropicProvider::new_with_key("sk-test").with_transport(mock),
+    /// );
+    ///
+    /// let request = LLMRequest::new("claude-3-5-haiku-20241022")
+    ///     .with_message(Message::user("List two colors as a JSON array"));
+    /// let mut colors = client.stream_json::<String>(&request).await?;
+    ///
+    /// assert_eq!(colors.next().await.unwrap()?, "red");
+    /// assert_eq!(colors.next().await.unwrap()?, "green");
+    /// assert!(colors.next().await.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stream_json<T: DeserializeOwned>(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<JsonStream<T>> {
+        Ok(self.send_request_stream(request).await?.json())
+    }
+
+    /// Simplified method to send a single user message.
+    ///
+    /// This is a convenience method for quick interactions. It uses the default model
+    /// set during `from_model()` construction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No default model was set (only works with `from_model()`)
+    /// - The HTTP request fails
+    /// - The API returns an error response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use turbine_llm::TurbineClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = TurbineClient::from_model("openai/gpt-4o-mini")?;
+    ///
+    /// // Simple one-liner
+    /// let response = client.send("What is Rust?").await?;
+    /// println!("{}", response.content);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send(&self, message: &str) -> Result<LLMResponse> {
+        let model = self.default_model.as_ref().ok_or_else(|| {
+            TurbineError::MissingField(
+                "No default model set. Use from_model() constructor or send_request() directly"
+                    .to_string(),
+            )
+        })?;
+
+        let request = LLMRequest::new(model).with_message(Message::user(message));
+
+        self.send_request(&request).await
+    }
+
+    /// Simplified method to send a message with a system prompt.
+    ///
+    /// Convenience method that combines system prompt and user message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use turbine_llm::TurbineClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = TurbineClient::from_model("anthropic/claude-3-5-sonnet")?;
+    ///
+    /// let response = client.send_with_system(
+    ///     "You are a Rust expert",
+    ///     "Explain ownership in one sentence"
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_with_system(
+        &self,
+        system_prompt: &str,
+        message: &str,
+    ) -> Result<LLMResponse> {
+        let model = self.default_model.as_ref().ok_or_else(|| {
+            TurbineError::MissingField(
+                "No default model set. Use from_model() constructor or send_request() directly"
+                    .to_string(),
+            )
+        })?;
+
+        let request = LLMRequest::new(model)
+            .with_system_prompt(system_prompt)
+            .with_message(Message::user(message));
+
+        self.send_request(&request).await
+    }
+
+    /// Asks `model` to keep writing `partial_assistant`, an unfinished reply to `history`,
+    /// and returns only the continuation.
+    ///
+    /// Anthropic continues the partial reply directly (prefill). Other providers get the
+    /// partial reply as an assistant turn followed by a user message asking to continue it;
+    /// if the model repeats the partial text anyway, it is removed from the response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{Message, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":" jumps over the lazy dog."}],"usage":{"input_tokens":20,"output_tokens":7}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone()));
+    ///
+    /// let history = [Message::user("Write a pangram")];
+    /// let response = client
+    ///     .continue_message("claude-3-5-haiku-20241022", &history, "The quick brown fox")
+    ///     .await?;
+    /// assert_eq!(response.content, " jumps over the lazy dog.");
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["messages"][1]["role"], "assistant");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn continue_message(
+        &self,
+        model: &str,
+        history: &[Message],
+        partial_assistant: &str,
+    ) -> Result<LLMResponse> {
+        let anthropic = self.provider_kind() == Some(Provider::Anthropic);
+        let messages = continuation(history, partial_assistant, anthropic);
+
+        let request = LLMRequest::new(model).with_messages(messages);
+        let mut response = self.send_request(&request).await?;
+        if let Some(continuation) = response.content.strip_prefix(partial_assistant) {
+            response.content = continuation.to_string();
+        }
+        Ok(response)
+    }
+}
+
+/// The user turn that asks non-Anthropic models to continue a partial reply.
+const CONTINUE_INSTRUCTION: &str = "Continue your previous message exactly where it stopped. \
+    Reply with only the continuation, without repeating any of it.";
+
+/// Returns `history` followed by the turns that ask the model to continue `partial`:
+/// a prefill for Anthropic, an assistant turn and [`CONTINUE_INSTRUCTION`] otherwise.
+fn continuation(history: &[Message], partial: &str, anthropic: bool) -> Vec<Message> {
+    let mut messages = history.to_vec();
+    if anthropic {
+        // Anthropic rejects a final assistant turn that ends with whitespace
+        messages.push(Message::assistant(partial.trim_end()));
+    } else {
+        messages.push(Message::assistant(partial));
+        messages.push(Message::user(CONTINUE_INSTRUCTION));
+    }
+    messages
+}
+
+/// HTTP protocol version used for provider connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpVersion {
+    /// Negotiate HTTP/2 over TLS when the server supports it, else HTTP/1.1 (the default)
+    #[default]
+    Auto,
+    /// Always use HTTP/1.1, e.g. behind proxies that break HTTP/2 streaming
+    Http1,
+    /// Always use HTTP/2 without negotiation; requires the `http2` feature
+    Http2,
+}
+
+/// Whether [`TurbineClient`] checks that a requested model belongs to its provider, see
+/// [`TurbineClient::with_model_validation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModelValidation {
+    /// Send every model name as is (the default)
+    #[default]
+    Off,
+    /// Send the request and report a mismatch in the response warnings
+    Warn,
+    /// Fail a mismatched request with [`TurbineError::InvalidRequest`] before sending it
+    Error,
+}
+
+/// Builds a [`TurbineClient`] with custom HTTP settings.
+///
+/// All providers built this way share the settings through a single `reqwest::Client`.
+/// Options that are not set keep the `reqwest` defaults.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use turbine_llm::{Provider, TurbineClient};
+///
+/// let client = TurbineClient::builder(Provider::OpenAI)
+///     .with_api_key("sk-xxx")
+///     .with_pool_max_idle_per_host(64)
+///     .with_pool_idle_timeout(Duration::from_secs(30))
+///     .build()?;
+/// # Ok::<(), turbine_llm::TurbineError>(())
+/// ```
+#[derive(Clone)]
+pub struct TurbineClientBuilder {
+    provider: Provider,
+    api_key: Option<String>,
+    api_key_env: Option<String>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    danger_accept_invalid_certs: bool,
+    key_provider: Option<KeyProvider>,
+    max_request_bytes: Option<usize>,
+    http_version: HttpVersion,
+    debug: bool,
+    region: Option<Region>,
+    base_url: Option<String>,
+}
+
+impl TurbineClientBuilder {
+    /// Creates a builder for `provider`.
+    pub fn new(provider: Provider) -> Self {
+        Self {
+            provider,
+            api_key: None,
+            api_key_env: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            danger_accept_invalid_certs: false,
+            key_provider: None,
+            max_request_bytes: None,
+            http_version: HttpVersion::Auto,
+            debug: false,
+            region: None,
+            base_url: None,
+        }
+    }
+
+    /// Sets the API key. Without it, the key is read from the provider's environment variable.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Reads the API key from the environment variable `name` instead of the provider's
+    /// default (see [`Provider::env_var`]). An explicit [`with_api_key`](Self::with_api_key)
+    /// takes precedence.
+    ///
+    /// # Example
+    ///
+    /// ```standalone_crate
+    /// use turbine_llm::{Provider, TurbineClient, TurbineError};
+    ///
+    /// // SAFETY: no other thread reads the environment yet
+    /// unsafe { std::env::set_var("TENANT_A_OPENAI_KEY", "sk-tenant-a") };
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key_env("TENANT_A_OPENAI_KEY")
+    ///     .build();
+    /// assert!(client.is_ok());
+    ///
+    /// let missing = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key_env("TENANT_B_OPENAI_KEY")
+    ///     .build();
+    /// assert!(matches!(missing, Err(TurbineError::ApiKeyNotFound(name)) if name == "TENANT_B_OPENAI_KEY"));
+    /// ```
+    pub fn with_api_key_env(mut self, name: impl Into<String>) -> Self {
+        self.api_key_env = Some(name.into());
+        self
+    }
+
+    /// Fetches the API key from `key_provider` before every request.
+    ///
+    /// See [`TurbineClient::with_key_provider`].
+    pub fn with_key_provider(mut self, key_provider: KeyProvider) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept open per host.
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long an idle connection is kept in the pool before being closed.
+    pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Disables TLS certificate verification. **Development only.**
+    ///
+    /// Use this solely to reach a local test server with a self-signed certificate. With
+    /// verification off, any certificate is trusted, exposing API keys and traffic to
+    /// interception. Defaults to `false`. Has no effect when the crate is built without a TLS
+    /// feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{Provider, TurbineClient};
+    ///
+    /// // Only against a local mock server
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key("sk-test")
+    ///     .with_danger_accept_invalid_certs(true)
+    ///     .build()?;
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn with_danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Rejects requests whose serialized body is larger than `max` bytes.
+    ///
+    /// Oversized requests fail with [`TurbineError::RequestTooLarge`] before anything is
+    /// sent, which catches runaway conversation growth without paying for a round trip.
+    /// There is no limit by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Provider, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key("sk-test")
+    ///     .with_max_request_bytes(64 * 1024)
+    ///     .build()?;
+    ///
+    /// let request = LLMRequest::quick("gpt-4o-mini", "x".repeat(100_000));
+    /// let error = client.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::RequestTooLarge { limit: 65536, .. }));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_max_request_bytes(mut self, max: usize) -> Self {
+        self.max_request_bytes = Some(max);
+        self
+    }
+
+    /// Selects the HTTP version. Defaults to [`HttpVersion::Auto`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{HttpVersion, Provider, TurbineClient};
+    ///
+    /// // Work around a proxy that stalls HTTP/2 streams
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key("sk-test")
+    ///     .with_http_version(HttpVersion::Http1)
+    ///     .build()?;
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn with_http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Pins requests to the provider's endpoint in `region`, for data residency.
+    ///
+    /// [`build`](Self::build) fails with [`TurbineError::InvalidRequest`] if the provider
+    /// has no endpoint in `region` (see [`Provider::regional_base_url`]), so requests never
+    /// silently leave the region.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{Provider, Region, TurbineClient, TurbineError};
+    ///
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key("sk-xxx")
+    ///     .with_region(Region::Eu)
+    ///     .build()?;
+    ///
+    /// let unsupported = TurbineClient::builder(Provider::Groq)
+    ///     .with_api_key("gsk-xxx")
+    ///     .with_region(Region::Eu)
+    ///     .build();
+    /// assert!(matches!(unsupported, Err(TurbineError::InvalidRequest(_))));
+    /// # Ok::<(), TurbineError>(())
+    /// ```
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Sends requests to `base_url` instead of the provider's API, e.g. a local test server
+    /// or a proxy. It takes precedence over [`with_region`](Self::with_region).
+    ///
+    /// See the providers' `with_base_url` for the expected form; for Gemini the API version
+    /// is appended to it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turbine_llm::{LLMRequest, Provider, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // A local mock server with a self-signed certificate
+    /// let client = TurbineClient::builder(Provider::OpenAI)
+    ///     .with_api_key("sk-test")
+    ///     .with_base_url("https://localhost:8443/v1")
+    ///     .with_danger_accept_invalid_certs(true)
+    ///     .build()?;
+    ///
+    /// client.send_request(&LLMRequest::quick("gpt-4o-mini", "Hello")).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Prints every request and response body to stderr when `debug` is `true`, with
+    /// authentication headers redacted. Off by default.
+    ///
+    /// Meant for interactive troubleshooting: the output includes full prompts and
+    /// completions. See [`DebugTransport`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{Provider, TurbineClient};
+    ///
+    /// let client = TurbineClient::builder(Provider::Anthropic)
+    ///     .with_api_key("sk-ant-xxx")
+    ///     .with_debug(true)
+    ///     .build()?;
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no API key was given and the environment variable is not set,
+    /// if the HTTP client cannot be created, if [`HttpVersion::Http2`] was selected
+    /// without the `http2` feature, or if the provider has no endpoint in the selected
+    /// region.
+    pub fn build(self) -> Result<TurbineClient> {
+        if let Some(region) = self.region
+            && self.provider.regional_base_url(region).is_none()
+        {
+            return Err(TurbineError::InvalidRequest(format!(
+                "{:?} has no endpoint in region {:?}",
+                self.provider, region
+            )));
+        }
+
+        // A key provider replaces the fixed key entirely
+        let api_key = match (&self.key_provider, self.api_key) {
+            (Some(_), _) => String::new(),
+            (None, Some(api_key)) => api_key,
+            (None, None) => match &self.api_key_env {
+                Some(name) => api_key_from_env_var(name)?,
+                None => self.provider.api_key_from_env()?,
+            },
+        };
+
+        let mut http_client = reqwest::Client::builder();
+        if let Some(max) = self.pool_max_idle_per_host {
+            http_client = http_client.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            http_client = http_client.pool_idle_timeout(timeout);
+        }
+        #[cfg(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        ))]
+        if self.danger_accept_invalid_certs {
+            http_client = http_client.danger_accept_invalid_certs(true);
+        }
+        #[cfg(not(any(
+            feature = "default-tls",
+            feature = "native-tls",
+            feature = "rustls-tls"
+        )))]
+        let _ = self.danger_accept_invalid_certs;
+        match self.http_version {
+            HttpVersion::Auto => {}
+            HttpVersion::Http1 => http_client = http_client.http1_only(),
+            #[cfg(feature = "http2")]
+            HttpVersion::Http2 => http_client = http_client.http2_prior_knowledge(),
+            #[cfg(not(feature = "http2"))]
+            HttpVersion::Http2 => {
+                return Err(TurbineError::InvalidRequest(
+                    "HttpVersion::Http2 requires the http2 feature".to_string(),
+                ));
+            }
+        }
+        let mut transport: Arc<dyn HttpTransport> =
+            Arc::new(ReqwestTransport::with_client(http_client.build()?));
+        if self.debug {
+            transport = Arc::new(DebugTransport::new(transport));
+        }
+
+        Ok(TurbineClient::from_parts(
+            provider_impl(
+                self.provider,
+                api_key,
+                self.key_provider,
+                transport,
+                self.region,
+                self.base_url,
+                self.max_request_bytes,
+            ),
+            None,
+        ))
+    }
+}
+
+/// Returns why the content of a JSON response does not parse, if it doesn't.
+///
+/// Tool-only turns carry no content and are not checked.
+fn json_error(response: &LLMResponse) -> Option<serde_json::Error> {
+    if response.tool_calls.is_some() && response.content.is_empty() {
+        return None;
+    }
+
+    serde_json::from_str::<serde_json::Value>(&response.content).err()
+}
+
+/// Checks that the content of a JSON response parses.
+fn validate_json(response: &LLMResponse) -> Result<()> {
+    match json_error(response) {
+        Some(err) => Err(TurbineError::InvalidResponse(format!(
+            "Response is not valid JSON ({}): {}",
+            err, response.content
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Creates the provider implementation with a fixed key, or with `key_provider` if given.
+///
+/// `region` is only applied to OpenAI; callers check that the provider supports it.
+/// `base_url` replaces the provider's endpoint, including a regional one.
+pub(crate) fn provider_impl(
+    provider: Provider,
+    api_key: String,
+    key_provider: Option<KeyProvider>,
+    transport: Arc<dyn HttpTransport>,
+    region: Option<Region>,
+    base_url: Option<String>,
+    max_request_bytes: Option<usize>,
+) -> Box<dyn LLMProviderTrait> {
+    match provider {
+        Provider::OpenAI => {
+            let mut provider = OpenAIProvider::new_with_key(api_key).with_transport(transport);
+            if let Some(region) = region {
+                provider = provider.with_region(region);
+            }
+            if let Some(base_url) = base_url {
+                provider = provider.with_base_url(base_url);
+            }
+            if let Some(max) = max_request_bytes {
+                provider = provider.with_max_request_bytes(max);
+            }
+            Box::new(match key_provider {
+                Some(key_provider) => provider.with_key_provider(key_provider),
+                None => provider,
+            })
+        }
+        Provider::Anthropic => {
+            let mut provider = AnthropicProvider::new_with_key(api_key).with_transport(transport);
+            if let Some(base_url) = base_url {
+                provider = provider.with_base_url(base_url);
+            }
+            if let Some(max) = max_request_bytes {
+                provider = provider.with_max_request_bytes(max);
+            }
+            Box::new(match key_provider {
+                Some(key_provider) => provider.with_key_provider(key_provider),
+                None => provider,
+            })
+        }
+        Provider::Gemini => {
+            let mut provider = GeminiProvider::new_with_key(api_key).with_transport(transport);
+            if let Some(base_url) = base_url {
+                provider = provider.with_base_url(base_url);
+            }
+            if let Some(max) = max_request_bytes {
+                provider = provider.with_max_request_bytes(max);
+            }
+            Box::new(match key_provider {
+                Some(key_provider) => provider.with_key_provider(key_provider),
+                None => provider,
+            })
+        }
+        Provider::Groq => {
+            let mut provider = GroqProvider::new_with_key(api_key).with_transport(transport);
+            if let Some(base_url) = base_url {
+                provider = provider.with_base_url(base_url);
+            }
+            if let Some(max) = max_request_bytes {
+                provider = provider.with_max_request_bytes(max);
+            }
+            Box::new(match key_provider {
+                Some(key_provider) => provider.with_key_provider(key_provider),
+                None => provider,
+            })
+        }
     }
 }
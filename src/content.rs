@@ -0,0 +1,273 @@
+//! Non-text message content such as images and documents.
+//!
+//! A [`Message`](crate::Message) always carries its text in `content`. Additional
+//! [`ContentPart`]s can be attached to it; each provider serializes them into its own
+//! native format and rejects the kinds it does not support with
+//! [`TurbineError::UnsupportedContent`].
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{Result, TurbineError};
+
+/// A non-text part of a message.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider, openai::OpenAIProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{ContentPart, DocumentSource, LLMRequest, Message, TurbineError};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let pdf = ContentPart::Document(DocumentSource::base64("application/pdf", "JVBERi0="));
+/// let request = LLMRequest::new("claude-3-5-sonnet-20241022")
+///     .with_message(Message::user("Summarize this").with_part(pdf));
+///
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"content":[{"type":"text","text":"A summary."}],"usage":{"input_tokens":9,"output_tokens":3}}"#,
+/// )));
+/// let anthropic = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+/// anthropic.send_request(&request).await?;
+///
+/// let body = mock.last_request().unwrap().json_body()?;
+/// assert_eq!(body["messages"][0]["content"][0]["type"], "document");
+/// assert_eq!(body["messages"][0]["content"][1]["text"], "Summarize this");
+///
+/// // OpenAI only accepts images
+/// let openai = OpenAIProvider::new_with_key("sk-test").with_transport(Arc::new(MockTransport::new()));
+/// assert!(matches!(
+///     openai.send_request(&request).await,
+///     Err(TurbineError::UnsupportedContent(_))
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    /// An image (supported by all providers)
+    Image(ImageSource),
+    /// A document such as a PDF (supported by Anthropic and Gemini)
+    Document(DocumentSource),
+}
+
+impl ContentPart {
+    /// Reads a local image or PDF file and builds the matching content part.
+    ///
+    /// The media type is detected from the file extension. Supported extensions are
+    /// `png`, `jpg`/`jpeg`, `gif`, `webp` and `pdf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its type is not supported.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let media_type = media_type_from_path(path)?;
+        let data = STANDARD.encode(std::fs::read(path)?);
+
+        Ok(if media_type == "application/pdf" {
+            ContentPart::Document(DocumentSource::base64(media_type, data))
+        } else {
+            ContentPart::Image(ImageSource::base64(media_type, data))
+        })
+    }
+
+    /// Returns the media type of this part (e.g. `"image/png"`).
+    pub fn media_type(&self) -> &str {
+        match self {
+            ContentPart::Image(image) => &image.media_type,
+            ContentPart::Document(document) => &document.media_type,
+        }
+    }
+}
+
+/// A base64-encoded image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageSource {
+    /// The image media type (e.g. `"image/png"`)
+    pub media_type: String,
+    /// The base64-encoded image bytes
+    pub data: String,
+    /// Resolution the model processes the image at (OpenAI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+}
+
+/// Resolution at which OpenAI processes an image, trading cost for detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    /// A fixed, low-resolution pass with a small token cost
+    Low,
+    /// Full resolution, billed by image size
+    High,
+    /// Let the model choose based on the image size
+    Auto,
+}
+
+impl ImageSource {
+    /// Creates an image from already base64-encoded data.
+    pub fn base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            media_type: media_type.into(),
+            data: data.into(),
+            detail: None,
+        }
+    }
+
+    /// Encodes raw image bytes, detecting the media type from their content.
+    ///
+    /// PNG, JPEG, GIF and WebP are recognized by their leading magic bytes, so the media
+    /// type always matches the data regardless of file names. The detected type is in
+    /// [`media_type`](Self::media_type); see also [`detect_image_media_type`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::UnsupportedContent`] if the bytes are not one of these
+    /// formats.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{ImageSource, TurbineError};
+    ///
+    /// let png = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 0x0d];
+    /// let image = ImageSource::from_bytes(&png)?;
+    /// assert_eq!(image.media_type, "image/png");
+    /// assert_eq!(image.data, "iVBORw0KGgoAAAAN");
+    ///
+    /// assert!(matches!(
+    ///     ImageSource::from_bytes(b"%PDF-1.7"),
+    ///     Err(TurbineError::UnsupportedContent(_))
+    /// ));
+    /// # Ok::<(), TurbineError>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let media_type = detect_image_media_type(bytes).ok_or_else(|| {
+            TurbineError::UnsupportedContent(
+                "Unrecognized image format. Supported: png, jpeg, gif, webp".to_string(),
+            )
+        })?;
+        Ok(Self::base64(media_type, STANDARD.encode(bytes)))
+    }
+
+    /// Sets the resolution OpenAI processes the image at. Other providers ignore it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{ContentPart, ImageDetail, ImageSource, LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"A cat"}}],"usage":{"prompt_tokens":90,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let image = ImageSource::base64("image/png", "iVBORw0KGgo=").with_detail(ImageDetail::Low);
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_message(Message::user("What is this?").with_part(ContentPart::Image(image)));
+    /// provider.send_request(&request).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["messages"][0]["content"][1]["image_url"]["detail"], "low");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_detail(mut self, detail: ImageDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// Returns the image as a `data:` URL.
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// A base64-encoded document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentSource {
+    /// The document media type (e.g. `"application/pdf"`)
+    pub media_type: String,
+    /// The base64-encoded document bytes
+    pub data: String,
+    /// Whether the model should cite this document (Anthropic)
+    #[serde(default)]
+    pub citations: bool,
+}
+
+impl DocumentSource {
+    /// Creates a document from already base64-encoded data.
+    pub fn base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            media_type: media_type.into(),
+            data: data.into(),
+            citations: false,
+        }
+    }
+
+    /// Enables citations for this document.
+    ///
+    /// Anthropic then returns the passages it relied on in
+    /// [`LLMResponse::citations`](crate::LLMResponse::citations). Other providers ignore this flag.
+    pub fn with_citations(mut self, enabled: bool) -> Self {
+        self.citations = enabled;
+        self
+    }
+}
+
+/// Detects the media type of PNG, JPEG, GIF and WebP images from their magic bytes.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::content::detect_image_media_type;
+///
+/// assert_eq!(detect_image_media_type(b"GIF89a..."), Some("image/gif"));
+/// assert_eq!(detect_image_media_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+/// assert_eq!(detect_image_media_type(b"plain text"), None);
+/// ```
+pub fn detect_image_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+fn media_type_from_path(path: &Path) -> Result<&'static str> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" => Ok("image/png"),
+        "jpg" | "jpeg" => Ok("image/jpeg"),
+        "gif" => Ok("image/gif"),
+        "webp" => Ok("image/webp"),
+        "pdf" => Ok("application/pdf"),
+        _ => Err(TurbineError::UnsupportedContent(format!(
+            "Cannot detect media type of {}. Supported: png, jpg, jpeg, gif, webp, pdf",
+            path.display()
+        ))),
+    }
+}
@@ -0,0 +1,188 @@
+//! Conversation history that can be saved to and loaded from JSON.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{
+    error::{Result, TurbineError},
+    models::{LLMRequest, LLMResponse, Message, Usage},
+};
+
+/// Version of the file format written by [`Conversation::save`].
+const FORMAT_VERSION: u32 = 1;
+
+/// A chat history with its model and system prompt.
+///
+/// Use [`to_request`](Self::to_request) to send it and [`push`](Self::push) to record each
+/// turn. [`save`](Self::save) and [`load`](Self::load) persist it as JSON, e.g. to resume a
+/// chat or inspect it while debugging.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::conversation::Conversation;
+/// use turbine_llm::Message;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut conversation = Conversation::new("gpt-4o-mini").with_system_prompt("Be brief.");
+/// conversation.push(Message::user("What is Rust?"));
+/// conversation.push(Message::assistant("A systems programming language."));
+///
+/// let path = std::env::temp_dir().join("turbine-conversation-example.json");
+/// conversation.save(&path)?;
+/// let restored = Conversation::load(&path)?;
+/// assert_eq!(restored.model, "gpt-4o-mini");
+/// assert_eq!(restored.system_prompt.as_deref(), Some("Be brief."));
+/// assert_eq!(restored.messages[1].content, "A systems programming language.");
+///
+/// let request = restored.to_request();
+/// assert_eq!(request.messages.len(), 2);
+/// # std::fs::remove_file(path)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    /// The model the conversation is held with
+    pub model: String,
+    /// Optional system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// The messages so far, oldest first
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    /// Usage of every response recorded with `push_response`
+    #[serde(default)]
+    usage: Usage,
+}
+
+/// The JSON document written by [`Conversation::save`].
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    conversation: Conversation,
+}
+
+impl Conversation {
+    /// Creates an empty conversation with `model`.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            system_prompt: None,
+            messages: Vec::new(),
+            usage: Usage::default(),
+        }
+    }
+
+    /// Sets the system prompt.
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Appends a message.
+    pub fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Appends the assistant turn of `response`, including its tool calls, and adds its
+    /// usage to [`cumulative_usage`](Self::cumulative_usage).
+    pub fn push_response(&mut self, response: &LLMResponse) {
+        self.usage += &response.usage;
+        let mut message = Message::assistant(response.content.clone());
+        if let Some(tool_calls) = &response.tool_calls {
+            message = message.with_tool_calls(tool_calls.clone());
+        }
+        self.messages.push(message);
+    }
+
+    /// Returns the total usage of all responses recorded with
+    /// [`push_response`](Self::push_response). It is saved along with the conversation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::conversation::Conversation;
+    /// use turbine_llm::{LLMResponse, Message};
+    ///
+    /// let mut conversation = Conversation::new("gpt-4o-mini");
+    /// conversation.push(Message::user("Hi"));
+    /// conversation.push_response(&LLMResponse::new("Hello!".to_string(), 8, 3));
+    /// conversation.push(Message::user("How are you?"));
+    /// conversation.push_response(&LLMResponse::new("Fine.".to_string(), 20, 2));
+    ///
+    /// let usage = conversation.cumulative_usage();
+    /// assert_eq!(usage.input_tokens, 28);
+    /// assert_eq!(usage.output_tokens, 5);
+    /// ```
+    pub fn cumulative_usage(&self) -> Usage {
+        self.usage.clone()
+    }
+
+    /// Returns the cost of [`cumulative_usage`](Self::cumulative_usage) in US dollars at the
+    /// list price of the conversation's model, or `None` if the model is unknown (see
+    /// [`Usage::estimated_cost`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::conversation::Conversation;
+    /// use turbine_llm::{LLMResponse, Message};
+    ///
+    /// let mut conversation = Conversation::new("gpt-4o");
+    /// conversation.push(Message::user("Summarize this report"));
+    /// conversation.push_response(&LLMResponse::new("...".to_string(), 600_000, 100_000));
+    /// conversation.push(Message::user("Shorter, please"));
+    /// conversation.push_response(&LLMResponse::new("...".to_string(), 400_000, 100_000));
+    ///
+    /// // 1M input tokens at $2.50 plus 200K output tokens at $10 per million
+    /// assert_eq!(conversation.cumulative_cost(), Some(4.5));
+    /// assert_eq!(Conversation::new("my-fine-tuned-model").cumulative_cost(), None);
+    /// ```
+    pub fn cumulative_cost(&self) -> Option<f64> {
+        self.usage.estimated_cost(&self.model)
+    }
+
+    /// Builds a request with the model, system prompt and messages.
+    pub fn to_request(&self) -> LLMRequest {
+        let mut request = LLMRequest::new(&self.model).with_messages(self.messages.clone());
+        request.system_prompt = self.system_prompt.clone();
+        request
+    }
+
+    /// Writes the conversation to `path` as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let envelope = Envelope {
+            version: FORMAT_VERSION,
+            conversation: self.clone(),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&envelope)?)?;
+        Ok(())
+    }
+
+    /// Reads a conversation written by [`save`](Self::save).
+    ///
+    /// Fields added by newer versions of this crate are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or was written in a newer
+    /// format version than this crate understands.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let envelope: Envelope = serde_json::from_slice(&std::fs::read(path)?)?;
+        if envelope.version > FORMAT_VERSION {
+            return Err(TurbineError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported conversation format version {} (expected at most {})",
+                    envelope.version, FORMAT_VERSION
+                ),
+            )));
+        }
+        Ok(envelope.conversation)
+    }
+}
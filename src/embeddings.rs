@@ -0,0 +1,158 @@
+//! Text embeddings.
+//!
+//! Embeddings are currently supported by the OpenAI provider. Other providers return
+//! [`TurbineError::InvalidRequest`] from
+//! [`TurbineClient::embed`](crate::TurbineClient::embed).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, TurbineError},
+    models::Usage,
+};
+
+/// Largest `dimensions` value of models that support shortening their vectors.
+const MAX_DIMENSIONS: &[(&str, u32)] = &[
+    ("text-embedding-3-small", 1536),
+    ("text-embedding-3-large", 3072),
+];
+
+/// Models that always return full-size vectors.
+const FIXED_DIMENSION_MODELS: &[&str] = &["text-embedding-ada-002"];
+
+/// A request to embed one or more texts.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::embeddings::EmbeddingRequest;
+/// use turbine_llm::providers::openai::OpenAIProvider;
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{TurbineClient, TurbineError};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"data":[{"index":0,"embedding":[0.1,-0.2,0.3]}],"usage":{"prompt_tokens":4,"total_tokens":4}}"#,
+/// )));
+/// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()));
+///
+/// let request = EmbeddingRequest::new("text-embedding-3-small")
+///     .with_input("Rust is fast")
+///     .with_dimensions(3);
+/// let response = client.embed(&request).await?;
+/// assert_eq!(response.embeddings[0], [0.1, -0.2, 0.3]);
+/// assert_eq!(mock.last_request().unwrap().json_body()?["dimensions"], 3);
+///
+/// // Out of range for the model
+/// let too_large = EmbeddingRequest::new("text-embedding-3-small")
+///     .with_input("Rust is fast")
+///     .with_dimensions(4096);
+/// assert!(matches!(client.embed(&too_large).await, Err(TurbineError::InvalidRequest(_))));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    /// The embedding model (e.g. "text-embedding-3-small")
+    pub model: String,
+    /// The texts to embed
+    pub input: Vec<String>,
+    /// Number of dimensions of the returned vectors, for models that support shortening
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+}
+
+impl EmbeddingRequest {
+    /// Creates a request for the specified model.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            input: Vec::new(),
+            dimensions: None,
+        }
+    }
+
+    /// Adds a text to embed.
+    pub fn with_input(mut self, text: impl Into<String>) -> Self {
+        self.input.push(text.into());
+        self
+    }
+
+    /// Adds several texts to embed.
+    pub fn with_inputs<I, S>(mut self, texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.input.extend(texts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Shortens the returned vectors to `dimensions` (OpenAI `text-embedding-3` models).
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Checks the request before it is sent.
+    ///
+    /// `dimensions` is checked against the model's supported range when the model is known.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.input.is_empty() {
+            return Err(TurbineError::MissingField(
+                "At least one input text is required".to_string(),
+            ));
+        }
+
+        let Some(dimensions) = self.dimensions else {
+            return Ok(());
+        };
+        if dimensions == 0 {
+            return Err(TurbineError::InvalidRequest(
+                "dimensions must be greater than 0".to_string(),
+            ));
+        }
+        if FIXED_DIMENSION_MODELS.contains(&self.model.as_str()) {
+            return Err(TurbineError::InvalidRequest(format!(
+                "{} does not support the dimensions parameter",
+                self.model
+            )));
+        }
+        match max_dimensions(&self.model) {
+            Some(max) if dimensions > max => Err(TurbineError::InvalidRequest(format!(
+                "{} supports at most {} dimensions, got {}",
+                self.model, max, dimensions
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the largest `dimensions` value `model` accepts, if known.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::embeddings::max_dimensions;
+///
+/// assert_eq!(max_dimensions("text-embedding-3-large"), Some(3072));
+/// assert_eq!(max_dimensions("my-embedder"), None);
+/// ```
+pub fn max_dimensions(model: &str) -> Option<u32> {
+    MAX_DIMENSIONS
+        .iter()
+        .find(|(name, _)| *name == model)
+        .map(|(_, max)| *max)
+}
+
+/// Embedding vectors returned by a provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    /// One vector per input text, in input order
+    pub embeddings: Vec<Vec<f32>>,
+    /// Token usage statistics (`output_tokens` is always 0)
+    pub usage: Usage,
+}
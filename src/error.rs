@@ -1,5 +1,10 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
+use crate::models::Usage;
+use crate::types::Provider;
+
 /// Error types for Turbine LLM operations.
 ///
 /// All errors that can occur when using the Turbine LLM library.
@@ -18,17 +23,84 @@ pub enum TurbineError {
     JsonError(#[from] serde_json::Error),
 
     /// API returned an error response
+    ///
+    /// Holds the provider's JSON error body. Bodies that are not JSON, such as an HTML page
+    /// from a proxy or gateway, are replaced by the status code and a short excerpt.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let page = "<html>\n  <head><title>502 Bad Gateway</title></head>\n  <body>nginx</body>\n</html>";
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(502, page)));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+    ///
+    /// let error = provider.send_request(&LLMRequest::quick("gpt-4o-mini", "Hi")).await.unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     TurbineError::ApiError(message) if message.starts_with("HTTP 502 with a non-JSON body: <html> <head>")
+    /// ));
+    /// # }
+    /// ```
     #[error("API returned error: {0}")]
     ApiError(String),
 
+    /// The provider is temporarily overloaded (Anthropic status 529); retrying later
+    /// usually succeeds
+    #[error("Provider overloaded: {0}")]
+    Overloaded(String),
+
+    /// The provider rejected the API key (status 401 or 403)
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// The provider's rate limit was hit (status 429); retrying later usually succeeds
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
     /// Response format is invalid or unexpected
     #[error("Invalid response format: {0}")]
     InvalidResponse(String),
 
+    /// The provider returned no output but still reported token usage, e.g. when Gemini
+    /// blocks a prompt or response for safety. The tokens may still be billed.
+    #[error("Empty response: {reason}")]
+    EmptyResponse {
+        /// Why no output was returned (e.g. the block or finish reason)
+        reason: String,
+        /// Tokens reported by the provider
+        usage: Usage,
+    },
+
+    /// API keys for several providers are set, so
+    /// [`Provider::from_env`](crate::Provider::from_env) cannot pick one
+    #[error("API keys for several providers are set ({providers:?}); choose one explicitly")]
+    AmbiguousProvider {
+        /// The providers whose API key is set
+        providers: Vec<Provider>,
+    },
+
     /// Environment variable error
     #[error("Environment variable error: {0}")]
     EnvError(#[from] std::env::VarError),
 
+    /// The request is invalid and was not sent
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// The serialized request body exceeds the configured limit and was not sent
+    #[error("Request body of {size} bytes exceeds the limit of {limit} bytes")]
+    RequestTooLarge {
+        /// Size of the serialized body in bytes
+        size: usize,
+        /// The configured maximum in bytes
+        limit: usize,
+    },
+
     /// Required field is missing
     #[error("Missing required field: {0}")]
     MissingField(String),
@@ -36,6 +108,213 @@ pub enum TurbineError {
     /// IO error
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// Message content is not supported (unknown file type, or not accepted by the provider)
+    #[error("Unsupported content: {0}")]
+    UnsupportedContent(String),
+
+    /// A stream failed after some content had arrived, or was cancelled while
+    /// [`ResponseStream::into_response`](crate::streaming::ResponseStream::into_response)
+    /// read it. `partial` holds the text received before, so long generations can be
+    /// salvaged or continued, and `usage` the tokens reported until then.
+    #[error("Stream interrupted: {source}")]
+    StreamInterrupted {
+        /// Text received before the failure
+        partial: String,
+        /// Tokens reported by the provider before the failure
+        usage: Usage,
+        /// The error that ended the stream
+        source: Box<TurbineError>,
+    },
+
+    /// An operation took longer than its configured limit. `reason` says which limit:
+    /// `"idle"` when a stream went silent between two chunks
+    #[error("Timed out ({reason}) after {limit:?}")]
+    Timeout {
+        /// Which limit was exceeded
+        reason: String,
+        /// The configured limit
+        limit: Duration,
+    },
+
+    /// A retryable error occurred, but waiting for the next retry would have exceeded the
+    /// [`RetryPolicy`](crate::retry::RetryPolicy) deadline
+    #[error("Retry deadline of {deadline:?} reached: {source}")]
+    DeadlineExceeded {
+        /// The configured deadline
+        deadline: Duration,
+        /// The error of the last attempt
+        source: Box<TurbineError>,
+    },
+
+    /// A model string has a provider prefix that is not supported
+    #[error(
+        "Unknown provider prefix: {prefix}. Supported: openai, anthropic, google, gemini, groq"
+    )]
+    UnknownProvider {
+        /// The prefix before the `/`
+        prefix: String,
+    },
+
+    /// The provider cannot be inferred from a model name without a prefix
+    #[error(
+        "Cannot infer provider from model name: {model}. Use format 'provider/model' (e.g., 'openai/gpt-4')"
+    )]
+    UnknownModel {
+        /// The model name
+        model: String,
+    },
+
+    /// A guardrail set with `TurbineClient::with_input_guard` or
+    /// `TurbineClient::with_output_guard` rejected the content
+    #[error("Rejected by guardrail: {reason}")]
+    Guardrail {
+        /// Why the guardrail rejected the content
+        reason: String,
+    },
+
+    /// The request was cancelled through a `CancellationToken` before it completed
+    /// (a single request, a stream, or a batch item)
+    #[error("Request cancelled")]
+    Cancelled,
+}
+
+impl TurbineError {
+    /// Returns whether the request may succeed if sent again: the provider was overloaded or
+    /// rate limited, or the connection failed or timed out (also when it interrupted a stream).
+    ///
+    /// This includes [`TurbineError::Timeout`], since a stalled server often recovers.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TurbineError::Overloaded(_) | TurbineError::RateLimited(_) => true,
+            TurbineError::HttpError(error) => error.is_timeout() || error.is_connect(),
+            TurbineError::Timeout { .. } => true,
+            TurbineError::StreamInterrupted { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Returns the HTTP status code that best describes the error, for services that pass
+    /// errors on to their own clients.
+    ///
+    /// | Status | Errors |
+    /// |--------|--------|
+    /// | 400 | invalid request, missing field, unsupported content, unknown provider or model, guardrail |
+    /// | 401 | API key not found or rejected by the provider |
+    /// | 413 | request too large |
+    /// | 429 | rate limited by the provider |
+    /// | 499 | cancelled |
+    /// | 502 | error response, invalid or empty response, failed connection |
+    /// | 503 | provider overloaded |
+    /// | 504 | timeout, retry deadline reached |
+    /// | 500 | anything else (local I/O, environment and configuration errors) |
+    ///
+    /// [`TurbineError::StreamInterrupted`] uses the status of the error that ended the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use turbine_llm::TurbineError;
+    ///
+    /// assert_eq!(TurbineError::ApiKeyNotFound("openai".into()).http_status(), 401);
+    /// assert_eq!(TurbineError::InvalidRequest("no messages".into()).http_status(), 400);
+    /// assert_eq!(TurbineError::ApiError("bad gateway".into()).http_status(), 502);
+    ///
+    /// let timeout = TurbineError::Timeout { reason: "idle".into(), limit: Duration::from_secs(30) };
+    /// assert_eq!(timeout.http_status(), 504);
+    /// ```
+    ///
+    /// Upstream authentication failures and rate limits keep their status:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(401, r#"{"error":{"message":"Incorrect API key"}}"#))
+    ///         .with_response(HttpResponse::new(429, r#"{"error":{"message":"Rate limit reached"}}"#)),
+    /// );
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+    /// let request = LLMRequest::quick("gpt-4o-mini", "Hi");
+    ///
+    /// let error = provider.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::Unauthorized(_)));
+    /// assert_eq!(error.http_status(), 401);
+    ///
+    /// let error = provider.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::RateLimited(_)));
+    /// assert_eq!(error.http_status(), 429);
+    /// assert!(error.is_retryable());
+    /// # }
+    /// ```
+    pub fn http_status(&self) -> u16 {
+        match self {
+            TurbineError::InvalidRequest(_)
+            | TurbineError::MissingField(_)
+            | TurbineError::UnsupportedContent(_)
+            | TurbineError::UnknownProvider { .. }
+            | TurbineError::UnknownModel { .. }
+            | TurbineError::Guardrail { .. } => 400,
+            TurbineError::ApiKeyNotFound(_) | TurbineError::Unauthorized(_) => 401,
+            TurbineError::RequestTooLarge { .. } => 413,
+            TurbineError::RateLimited(_) => 429,
+            TurbineError::Cancelled => 499,
+            TurbineError::HttpError(error) if error.is_timeout() => 504,
+            TurbineError::HttpError(_)
+            | TurbineError::ApiError(_)
+            | TurbineError::InvalidResponse(_)
+            | TurbineError::EmptyResponse { .. }
+            | TurbineError::JsonError(_) => 502,
+            TurbineError::Overloaded(_) => 503,
+            TurbineError::Timeout { .. } | TurbineError::DeadlineExceeded { .. } => 504,
+            TurbineError::StreamInterrupted { source, .. } => source.http_status(),
+            TurbineError::EnvError(_)
+            | TurbineError::IoError(_)
+            | TurbineError::AmbiguousProvider { .. } => 500,
+        }
+    }
+
+    /// Returns the token usage the provider reported along with the error, if any: for an
+    /// empty response, or for a stream that failed or was cancelled midway.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"promptFeedback":{"blockReason":"SAFETY"},
+    ///         "usageMetadata":{"promptTokenCount":12,"totalTokenCount":12}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new_with_key("sk-test").with_transport(mock);
+    ///
+    /// let error = provider
+    ///     .send_request(&LLMRequest::quick("gemini-2.0-flash", "..."))
+    ///     .await
+    ///     .unwrap_err();
+    /// assert!(matches!(error, TurbineError::EmptyResponse { .. }));
+    /// assert_eq!(error.usage().unwrap().input_tokens, 12);
+    /// # }
+    /// ```
+    pub fn usage(&self) -> Option<&Usage> {
+        match self {
+            TurbineError::EmptyResponse { usage, .. }
+            | TurbineError::StreamInterrupted { usage, .. } => Some(usage),
+            _ => None,
+        }
+    }
 }
 
 /// Convenience type alias for Results that may return [`TurbineError`].
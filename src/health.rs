@@ -0,0 +1,75 @@
+//! Provider health checks.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, TurbineError},
+    transport::{HttpRequest, HttpTransport},
+};
+
+/// Whether a provider's API is reachable and accepts the configured credentials.
+///
+/// Returned by [`TurbineClient::health_check`](crate::TurbineClient::health_check).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// The API answered and accepted the API key
+    Healthy {
+        /// Round-trip time of the probe
+        latency: Duration,
+    },
+    /// The API answered but rejected the API key (HTTP 401 or 403)
+    AuthFailed {
+        /// The provider's error message
+        message: String,
+    },
+    /// The API could not be reached: DNS, connection or timeout failure
+    Unreachable {
+        /// Description of the network error
+        message: String,
+    },
+    /// The API answered with another error status, e.g. 5xx during an outage
+    Unavailable {
+        /// The HTTP status code
+        status: u16,
+        /// The provider's error message
+        message: String,
+    },
+}
+
+impl HealthStatus {
+    /// Returns `true` for [`HealthStatus::Healthy`].
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy { .. })
+    }
+}
+
+/// Sends a cheap authenticated request and classifies the outcome.
+pub(crate) async fn probe(
+    transport: &dyn HttpTransport,
+    request: HttpRequest,
+) -> Result<HealthStatus> {
+    let started = Instant::now();
+    let response = match transport.send(request).await {
+        Ok(response) => response,
+        Err(err @ (TurbineError::HttpError(_) | TurbineError::IoError(_))) => {
+            return Ok(HealthStatus::Unreachable {
+                message: err.to_string(),
+            });
+        }
+        Err(err) => return Err(err),
+    };
+    let latency = started.elapsed();
+
+    let message = || String::from_utf8_lossy(&response.body).into_owned();
+    Ok(match response.status {
+        _ if response.is_success() => HealthStatus::Healthy { latency },
+        401 | 403 => HealthStatus::AuthFailed { message: message() },
+        status => HealthStatus::Unavailable {
+            status,
+            message: message(),
+        },
+    })
+}
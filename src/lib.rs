@@ -64,7 +64,7 @@
 //!
 //! - **OpenAI**: `OPENAI_API_KEY`
 //! - **Anthropic**: `ANTHROPIC_API_KEY`
-//! - **Gemini**: `GEMINI_API_KEY`
+//! - **Gemini**: `GEMINI_API_KEY` (falls back to `GOOGLE_API_KEY`)
 //! - **Groq**: `GROQ_API_KEY`
 //!
 //! ## JSON Output
@@ -88,14 +88,43 @@
 //! # }
 //! ```
 
+pub mod audit;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod catalog;
 pub mod client;
+pub mod content;
+pub mod conversation;
+pub mod embeddings;
 pub mod error;
+pub mod health;
 pub mod models;
+pub mod multi;
+pub mod prompt;
 pub mod providers;
+pub mod rate_limit;
+pub mod retry;
+pub mod streaming;
+pub mod tokens;
+pub mod tools;
+pub mod transport;
 pub mod types;
+pub mod weighted;
 
 // Re-export commonly used types for convenience
-pub use client::TurbineClient;
+pub use catalog::{KnownModel, Tier};
+pub use client::{HttpVersion, ModelValidation, TurbineClient, TurbineClientBuilder};
+pub use content::{ContentPart, DocumentSource, ImageDetail, ImageSource};
 pub use error::{Result, TurbineError};
-pub use models::{LLMRequest, LLMResponse, Message, Usage};
-pub use types::{OutputFormat, Provider};
+pub use health::HealthStatus;
+pub use models::{
+    Choice, Citation, DEFAULT_MAX_TOKENS, LLMRequest, LLMResponse, Message, ResponseMetrics,
+    TruncationStrategy, Usage,
+};
+pub use multi::MultiClient;
+pub use rate_limit::Priority;
+pub use streaming::{JsonStream, ProviderEvent, RawEvent, ResponseStream, StreamChunk};
+pub use tokio_util::sync::CancellationToken;
+pub use tools::{Tool, ToolCall};
+pub use types::{FinishReason, OutputFormat, Provider, Region};
+pub use weighted::WeightedClient;
@@ -1,10 +1,20 @@
-use crate::types::OutputFormat;
+use crate::{
+    catalog::{self, Tier, TierTable},
+    content::{ContentPart, ImageSource},
+    error::Result,
+    rate_limit::Priority,
+    tokens,
+    tools::{Tool, ToolCall},
+    types::{FinishReason, OutputFormat, Provider},
+};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
 
 /// A chat message with a role and content.
 ///
 /// Messages represent individual turns in a conversation. Each message has a role
-/// (user, assistant, or system) and textual content.
+/// (user, assistant, system, or tool) and textual content.
 ///
 /// # Example
 ///
@@ -21,6 +31,15 @@ pub struct Message {
     pub role: String,
     /// The text content of the message
     pub content: String,
+    /// Additional non-text parts such as images or documents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
+    /// Tool calls made by the model in an assistant message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
 impl Message {
@@ -39,6 +58,9 @@ impl Message {
         Self {
             role: role.into(),
             content: content.into(),
+            parts: Vec::new(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
         }
     }
 
@@ -83,8 +105,187 @@ impl Message {
     pub fn system(content: impl Into<String>) -> Self {
         Self::new("system", content)
     }
+
+    /// Creates a message carrying the result of a tool call.
+    ///
+    /// Providers serialize it natively: OpenAI and Groq as a `tool` message with
+    /// `tool_call_id`, Anthropic as a `tool_result` block, and Gemini as a `functionResponse`
+    /// part. Add the assistant message with the calls before it (see
+    /// [`Message::with_tool_calls`]). For Anthropic, images and documents attached with
+    /// [`with_part`](Self::with_part) are sent inside the `tool_result` block, after its
+    /// text, e.g. the screenshot taken by a browser tool.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use serde_json::json;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{ContentPart, ImageSource, LLMRequest, Message, ToolCall};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let call = ToolCall {
+    ///     id: "call_1".to_string(),
+    ///     name: "get_weather".to_string(),
+    ///     arguments: json!({"city": "Paris"}),
+    /// };
+    /// let request = LLMRequest::new("gpt-4o-mini").with_messages(vec![
+    ///     Message::user("Weather in Paris?"),
+    ///     Message::assistant("").with_tool_calls(vec![call]),
+    ///     Message::tool_result("call_1", r#"{"temperature": 21}"#),
+    /// ]);
+    ///
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"It is 21°C."}}],"usage":{"prompt_tokens":30,"completion_tokens":5}}"#,
+    /// )));
+    /// OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()).send_request(&request).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["messages"][1]["tool_calls"][0]["function"]["arguments"], r#"{"city":"Paris"}"#);
+    /// assert_eq!(body["messages"][2]["role"], "tool");
+    /// assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+    ///
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"It is 21°C."}],"usage":{"input_tokens":30,"output_tokens":5}}"#,
+    /// )));
+    /// AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone()).send_request(&request).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+    /// assert_eq!(body["messages"][2]["role"], "user");
+    /// assert_eq!(body["messages"][2]["content"][0]["type"], "tool_result");
+    /// assert_eq!(body["messages"][2]["content"][0]["tool_use_id"], "call_1");
+    ///
+    /// // A tool result with text and an image
+    /// let screenshot = ContentPart::Image(ImageSource::base64("image/png", "iVBORw0KGgo="));
+    /// let request = LLMRequest::new("claude-3-5-sonnet-20241022").with_messages(vec![
+    ///     Message::user("Open the dashboard"),
+    ///     Message::assistant("").with_tool_calls(vec![ToolCall {
+    ///         id: "toolu_1".to_string(),
+    ///         name: "browse".to_string(),
+    ///         arguments: json!({"url": "https://example.com"}),
+    ///     }]),
+    ///     Message::tool_result("toolu_1", "Page loaded").with_part(screenshot),
+    /// ]);
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"Done."}],"usage":{"input_tokens":900,"output_tokens":2}}"#,
+    /// )));
+    /// AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone()).send_request(&request).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// let result = &body["messages"][2]["content"][0]["content"];
+    /// assert_eq!(result[0]["text"], "Page loaded");
+    /// assert_eq!(result[1]["type"], "image");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new("tool", content)
+        }
+    }
+
+    /// Creates a user message with a local image or PDF file attached.
+    ///
+    /// The file is read, its media type detected from the extension, and its contents
+    /// base64-encoded. Anthropic accepts images and PDFs, Gemini accepts images and PDFs,
+    /// and OpenAI and Groq accept images only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its type is not supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{ContentPart, Message};
+    ///
+    /// let path = std::env::temp_dir().join("turbine_user_with_file.png");
+    /// std::fs::write(&path, b"\x89PNG\r\n\x1a\n")?;
+    ///
+    /// let msg = Message::user_with_file("What is in this image?", &path)?;
+    /// assert_eq!(msg.content, "What is in this image?");
+    /// assert!(matches!(&msg.parts[0], ContentPart::Image(image) if image.media_type == "image/png"));
+    ///
+    /// assert!(Message::user_with_file("Read this", "notes.txt").is_err());
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn user_with_file(text: impl Into<String>, path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::user(text).with_part(ContentPart::from_file(path)?))
+    }
+
+    /// Creates a user message with several images, e.g. to compare them.
+    ///
+    /// The text comes first, followed by the images in the given order; each provider sends
+    /// them as separate image entries in one turn.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{ImageSource, LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = Message::user_with_images(
+    ///     "Spot the difference",
+    ///     vec![
+    ///         ImageSource::base64("image/png", "iVBORw0KGgo="),
+    ///         ImageSource::base64("image/jpeg", "/9j/4AAQ"),
+    ///     ],
+    /// );
+    /// assert_eq!(message.parts.len(), 2);
+    ///
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"The sky"}}],"usage":{"prompt_tokens":9,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    /// provider.send_request(&LLMRequest::new("gpt-4o").with_message(message)).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// let content = body["messages"][0]["content"].as_array().unwrap();
+    /// assert_eq!(content[0]["type"], "text");
+    /// assert_eq!(content[1]["image_url"]["url"], "data:image/png;base64,iVBORw0KGgo=");
+    /// assert_eq!(content[2]["image_url"]["url"], "data:image/jpeg;base64,/9j/4AAQ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn user_with_images(text: impl Into<String>, images: Vec<ImageSource>) -> Self {
+        let mut message = Self::user(text);
+        message
+            .parts
+            .extend(images.into_iter().map(ContentPart::Image));
+        message
+    }
+
+    /// Attaches a non-text content part to the message.
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Records the tool calls the model made in this assistant message.
+    ///
+    /// Use it to replay [`LLMResponse::tool_calls`] in the conversation history.
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
 }
 
+/// `max_tokens` sent for an [`LLMRequest`] that does not set one.
+///
+/// Long answers stop at this limit with [`FinishReason::Length`].
+/// Raise it per request with [`LLMRequest::with_max_tokens`], or per client with
+/// [`TurbineClient::with_default_max_tokens`](crate::TurbineClient::with_default_max_tokens).
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
 /// A request to send to an LLM provider.
 ///
 /// Use the builder pattern to construct requests with various parameters.
@@ -110,38 +311,115 @@ pub struct LLMRequest {
     pub messages: Vec<Message>,
     /// Optional system prompt to guide the model's behavior
     pub system_prompt: Option<String>,
-    /// Maximum number of tokens to generate (default: 1024)
+    /// Maximum number of tokens to generate (`None` uses the client's default, or
+    /// [`DEFAULT_MAX_TOKENS`]; see [`effective_max_tokens`](Self::effective_max_tokens))
     pub max_tokens: Option<u32>,
+    /// Maximum number of tokens to generate, including reasoning (OpenAI
+    /// `max_completion_tokens`, sent instead of `max_tokens` when set)
+    pub max_completion_tokens: Option<u32>,
     /// Sampling temperature from 0.0 to 2.0 (higher = more random)
     pub temperature: Option<f32>,
     /// Nucleus sampling threshold (0.0 to 1.0)
     pub top_p: Option<f32>,
-    /// Output format: text or JSON
-    pub output_format: OutputFormat,
+    /// Output format: text or JSON (`None` uses the client's default format, or text; see
+    /// [`effective_output_format`](Self::effective_output_format))
+    pub output_format: Option<OutputFormat>,
+    /// Tools the model may call
+    pub tools: Vec<Tool>,
+    /// Whether [`TurbineClient`](crate::TurbineClient) checks that JSON output parses
+    pub json_validation: bool,
+    /// How many corrective follow-ups to send when JSON output does not parse
+    pub json_repair_attempts: u32,
+    /// Whether OpenAI, Groq and Anthropic append a "respond with JSON" instruction to the
+    /// system prompt for JSON output (default: true)
+    pub auto_json_instruction: bool,
+    /// Replaces the wording of that instruction (`None` uses the provider's default)
+    pub json_instruction: Option<String>,
+    /// Number of alternative responses to generate (Gemini `candidateCount`)
+    pub candidate_count: Option<u32>,
+    /// Processing tier (OpenAI `service_tier`)
+    pub service_tier: Option<String>,
+    /// Opaque identifier of the end user (Anthropic `metadata.user_id`)
+    pub anthropic_user_id: Option<String>,
+    /// Order among requests waiting for the client's rate limit (not sent to the provider)
+    pub priority: Priority,
+    /// Whether [`TurbineClient`](crate::TurbineClient) moves `<think>` blocks out of the
+    /// content into [`LLMResponse::reasoning`]
+    pub extract_thinking: bool,
 }
 
 impl LLMRequest {
     /// Creates a new request for the specified model.
     ///
+    /// `max_tokens` starts unset and is sent as [`DEFAULT_MAX_TOKENS`].
+    ///
     /// # Example
     ///
     /// ```
-    /// use turbine_llm::LLMRequest;
+    /// use turbine_llm::{DEFAULT_MAX_TOKENS, LLMRequest};
     ///
     /// let request = LLMRequest::new("gpt-4o-mini");
+    /// assert_eq!(request.max_tokens, None);
+    /// assert_eq!(request.effective_max_tokens(), DEFAULT_MAX_TOKENS);
     /// ```
     pub fn new(model: impl Into<String>) -> Self {
         Self {
             model: model.into(),
             messages: Vec::new(),
             system_prompt: None,
-            max_tokens: Some(1024),
+            max_tokens: None,
+            max_completion_tokens: None,
             temperature: None,
             top_p: None,
-            output_format: OutputFormat::Text,
+            output_format: None,
+            tools: Vec::new(),
+            json_validation: false,
+            json_repair_attempts: 0,
+            auto_json_instruction: true,
+            json_instruction: None,
+            candidate_count: None,
+            service_tier: None,
+            anthropic_user_id: None,
+            priority: Priority::Normal,
+            extract_thinking: false,
         }
     }
 
+    /// Creates a request for `model` with a single user message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// let request = LLMRequest::quick("gpt-4o-mini", "Hello!");
+    /// assert_eq!(request.messages[0].content, "Hello!");
+    ///
+    /// // Equivalent, from a (model, text) tuple
+    /// let request: LLMRequest = ("gpt-4o-mini", "Hello!").into();
+    /// assert_eq!(request.messages[0].role, "user");
+    /// ```
+    pub fn quick(model: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::new(model).with_message(Message::user(text))
+    }
+
+    /// Creates a request for the built-in model of `provider` suited to `tier`.
+    ///
+    /// See [`TierTable`] for the models and for overriding them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Provider, Tier};
+    ///
+    /// assert_eq!(LLMRequest::for_task(Provider::OpenAI, Tier::Fast).model, "gpt-4o-mini");
+    /// assert_eq!(LLMRequest::for_task(Provider::Anthropic, Tier::Fast).model, "claude-3-5-haiku-20241022");
+    /// assert_eq!(LLMRequest::for_task(Provider::Gemini, Tier::Powerful).model, "gemini-2.5-pro");
+    /// ```
+    pub fn for_task(provider: Provider, tier: Tier) -> Self {
+        TierTable::default().request(provider, tier)
+    }
+
     /// Adds a single message to the request.
     ///
     /// # Example
@@ -177,6 +455,24 @@ impl LLMRequest {
         self
     }
 
+    /// Appends messages after the existing ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_message(Message::user("Hello!"))
+    ///     .with_added_messages(vec![Message::assistant("Hi!"), Message::user("How are you?")]);
+    /// assert_eq!(request.messages.len(), 3);
+    /// assert_eq!(request.messages[0].content, "Hello!");
+    /// ```
+    pub fn with_added_messages(mut self, messages: impl IntoIterator<Item = Message>) -> Self {
+        self.messages.extend(messages);
+        self
+    }
+
     /// Sets the system prompt to guide the model's behavior.
     ///
     /// # Example
@@ -194,6 +490,9 @@ impl LLMRequest {
 
     /// Sets the maximum number of tokens to generate.
     ///
+    /// This overrides a client's
+    /// [`with_default_max_tokens`](crate::TurbineClient::with_default_max_tokens).
+    ///
     /// # Example
     ///
     /// ```
@@ -207,6 +506,84 @@ impl LLMRequest {
         self
     }
 
+    /// Returns the `max_tokens` the request is sent with: its own, or
+    /// [`DEFAULT_MAX_TOKENS`] when it has none.
+    ///
+    /// [`TurbineClient`](crate::TurbineClient) fills in its
+    /// [default](crate::TurbineClient::with_default_max_tokens) before this is read.
+    pub fn effective_max_tokens(&self) -> u32 {
+        self.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)
+    }
+
+    /// Sets OpenAI's `max_completion_tokens`, the limit that also covers reasoning tokens.
+    ///
+    /// OpenAI's reasoning models reject the legacy `max_tokens`, so when both are set only
+    /// `max_completion_tokens` is sent. Other providers ignore it and keep using
+    /// `max_tokens`.
+    ///
+    /// Requests to o-series models (`o1`, `o3`, `o4` and their variants) that only set
+    /// `max_tokens`, including the [`DEFAULT_MAX_TOKENS`] of an unset one, send it as
+    /// `max_completion_tokens` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::LLMRequest;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"42"}}],
+    ///         "usage":{"prompt_tokens":3,"completion_tokens":1}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::quick("o4-mini", "What is six times seven?")
+    ///     .with_max_tokens(500)
+    ///     .with_max_completion_tokens(4000);
+    /// provider.send_request(&request).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["max_completion_tokens"], 4000);
+    /// assert!(body.get("max_tokens").is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Without it, the `max_tokens` of an o-series request is renamed:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::{DEFAULT_MAX_TOKENS, LLMRequest};
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"42"}}],
+    ///         "usage":{"prompt_tokens":3,"completion_tokens":1}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// provider.send_request(&LLMRequest::quick("o3-mini", "What is six times seven?")).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["max_completion_tokens"], DEFAULT_MAX_TOKENS);
+    /// assert!(body.get("max_tokens").is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_max_completion_tokens(mut self, max_completion_tokens: u32) -> Self {
+        self.max_completion_tokens = Some(max_completion_tokens);
+        self
+    }
+
     /// Sets the sampling temperature (0.0 to 2.0).
     ///
     /// Higher values make output more random, lower values more deterministic.
@@ -241,6 +618,10 @@ impl LLMRequest {
 
     /// Sets the output format (Text or Json).
     ///
+    /// This overrides a client's
+    /// [`with_default_output_format`](crate::TurbineClient::with_default_output_format), also
+    /// when the format is `Text`.
+    ///
     /// # Example
     ///
     /// ```
@@ -250,20 +631,791 @@ impl LLMRequest {
     ///     .with_output_format(OutputFormat::Json);
     /// ```
     pub fn with_output_format(mut self, format: OutputFormat) -> Self {
-        self.output_format = format;
+        self.output_format = Some(format);
         self
     }
+
+    /// Returns the output format the request is sent with: its own, or text when it has
+    /// none.
+    ///
+    /// [`TurbineClient`](crate::TurbineClient) fills in its
+    /// [default format](crate::TurbineClient::with_default_output_format) before this is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, OutputFormat};
+    ///
+    /// let mut request = LLMRequest::new("gpt-4o-mini");
+    /// assert_eq!(request.effective_output_format(), &OutputFormat::Text);
+    ///
+    /// request.output_format = Some(OutputFormat::Json);
+    /// assert_eq!(request.effective_output_format(), &OutputFormat::Json);
+    /// ```
+    pub fn effective_output_format(&self) -> &OutputFormat {
+        static TEXT: OutputFormat = OutputFormat::Text;
+        self.output_format.as_ref().unwrap_or(&TEXT)
+    }
+
+    /// Enables validation of JSON output.
+    ///
+    /// When enabled and the output format is [`OutputFormat::Json`] or
+    /// [`OutputFormat::JsonSchema`],
+    /// [`TurbineClient::send_request`](crate::TurbineClient::send_request) parses the
+    /// response content and returns [`TurbineError::InvalidResponse`](crate::TurbineError::InvalidResponse)
+    /// with the offending content if it is not valid JSON.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, OutputFormat, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"{\"name\": \"Ru"}}],"usage":{"prompt_tokens":9,"completion_tokens":4}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .with_json_validation(true)
+    ///     .with_message(Message::user("Name a language as JSON"));
+    /// let err = client.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(&err, TurbineError::InvalidResponse(msg) if msg.contains(r#"{"name": "Ru"#)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_json_validation(mut self, enabled: bool) -> Self {
+        self.json_validation = enabled;
+        self
+    }
+
+    /// Re-prompts up to `max_attempts` times when JSON output does not parse.
+    ///
+    /// Implies [`with_json_validation(true)`](Self::with_json_validation). After an invalid
+    /// response, [`TurbineClient::send_request`](crate::TurbineClient::send_request) appends
+    /// it to the conversation together with the parse error and asks for valid JSON only.
+    /// The returned usage covers all attempts. If the last attempt is still invalid, its
+    /// [`TurbineError::InvalidResponse`](crate::TurbineError::InvalidResponse) is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, OutputFormat, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(
+    ///             200,
+    ///             r#"{"choices":[{"message":{"content":"Sure! {name: Rust}"}}],
+    ///                 "usage":{"prompt_tokens":9,"completion_tokens":6,"prompt_tokens_details":{"cached_tokens":8}}}"#,
+    ///         ))
+    ///         .with_response(HttpResponse::new(
+    ///             200,
+    ///             r#"{"choices":[{"message":{"content":"{\"name\": \"Rust\"}"}}],
+    ///                 "usage":{"prompt_tokens":30,"completion_tokens":5,"prompt_tokens_details":{"cached_tokens":8}}}"#,
+    ///         )),
+    /// );
+    /// let client = TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone()));
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .with_json_repair(1)
+    ///     .with_message(Message::user("Name a language as JSON"));
+    /// let response = client.send_request(&request).await?;
+    /// assert_eq!(response.content, r#"{"name": "Rust"}"#);
+    /// assert_eq!(response.usage.input_tokens, 39);
+    /// assert_eq!(response.usage.cached_input_tokens, Some(16));
+    ///
+    /// let follow_up = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(follow_up["messages"][1]["content"], "Sure! {name: Rust}");
+    /// assert!(follow_up["messages"][2]["content"].as_str().unwrap().starts_with("Your last response was invalid JSON"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_json_repair(mut self, max_attempts: u32) -> Self {
+        self.json_repair_attempts = max_attempts;
+        self
+    }
+
+    /// Controls whether a JSON instruction is added to the system prompt for JSON output.
+    ///
+    /// By default OpenAI, Groq and Anthropic append "You must respond with valid JSON only."
+    /// (plus a hint to start with `{` for Anthropic) to the system prompt. Disable it to send
+    /// your system prompt verbatim and rely on the native JSON mode alone. Gemini never adds
+    /// an instruction. Note that OpenAI's JSON mode rejects requests whose messages do not
+    /// mention JSON.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, OutputFormat};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"{}"}}],"usage":{"prompt_tokens":9,"completion_tokens":1}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_system_prompt("Reply with a JSON object describing the city.")
+    ///     .with_message(Message::user("Paris"))
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .with_auto_json_instruction(false);
+    /// provider.send_request(&request).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["messages"][0]["content"], "Reply with a JSON object describing the city.");
+    /// assert_eq!(body["response_format"]["type"], "json_object");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_auto_json_instruction(mut self, enabled: bool) -> Self {
+        self.auto_json_instruction = enabled;
+        self
+    }
+
+    /// Replaces the JSON instruction added to the system prompt for JSON output.
+    ///
+    /// Use this when the default wording conflicts with your own schema instructions. The
+    /// text is appended where the default would be (see
+    /// [`with_auto_json_instruction`](Self::with_auto_json_instruction)), so it has no
+    /// effect when the instruction is disabled or for Gemini.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, OutputFormat};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"[]"}],"usage":{"input_tokens":9,"output_tokens":1}}"#,
+    /// )));
+    /// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("claude-3-5-haiku-20241022")
+    ///     .with_system_prompt("List the cities mentioned.")
+    ///     .with_message(Message::user("Paris and Rome"))
+    ///     .with_output_format(OutputFormat::Json)
+    ///     .with_json_instruction("Respond with a JSON array of strings only.");
+    /// provider.send_request(&request).await?;
+    ///
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(
+    ///     body["system"],
+    ///     "List the cities mentioned. Respond with a JSON array of strings only."
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_json_instruction(mut self, instruction: impl Into<String>) -> Self {
+        self.json_instruction = Some(instruction.into());
+        self
+    }
+
+    /// Requests `count` alternative responses (Gemini, and OpenAI as `n`).
+    ///
+    /// The first one fills [`LLMResponse::content`]; all of them are returned in
+    /// [`LLMResponse::choices`] with their finish reasons. Streams and providers without
+    /// support return a single response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{FinishReason, LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"candidates":[
+    ///         {"content":{"parts":[{"text":"A fox"}]},"finishReason":"STOP"},
+    ///         {"content":{"parts":[{"text":"A heron"}]},"finishReason":"MAX_TOKENS"}],
+    ///         "usageMetadata":{"promptTokenCount":6,"candidatesTokenCount":5}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("gemini-2.0-flash")
+    ///     .with_candidate_count(2)
+    ///     .with_message(Message::user("Name an animal"));
+    /// let response = provider.send_request(&request).await?;
+    ///
+    /// assert_eq!(response.content, "A fox");
+    /// let choices = response.choices.unwrap();
+    /// assert_eq!(choices[1].content, "A heron");
+    /// assert_eq!(choices[1].finish_reason, Some(FinishReason::Length));
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["generationConfig"]["candidateCount"], 2);
+    ///
+    /// // OpenAI works the same way
+    /// use turbine_llm::providers::openai::OpenAIProvider;
+    ///
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[
+    ///         {"message":{"content":"A fox"},"finish_reason":"stop"},
+    ///         {"message":{"content":"A her"},"finish_reason":"length"}],
+    ///         "usage":{"prompt_tokens":6,"completion_tokens":5}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_candidate_count(2)
+    ///     .with_message(Message::user("Name an animal"));
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(response.content, "A fox");
+    /// let choices = response.choices.unwrap();
+    /// assert_eq!(choices[1].finish_reason, Some(FinishReason::Length));
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["n"], 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_candidate_count(mut self, count: u32) -> Self {
+        self.candidate_count = Some(count);
+        self
+    }
+
+    /// Sets the OpenAI processing tier: `"auto"`, `"default"`, `"flex"` or `"priority"`.
+    ///
+    /// `"flex"` trades latency for a lower price, which suits background jobs. Other
+    /// providers ignore the tier. OpenAI rejects unknown values with
+    /// [`TurbineError::InvalidRequest`](crate::TurbineError::InvalidRequest) before sending.
+    /// The tier that served the request is returned in
+    /// [`LLMResponse::service_tier`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"Done"}}],"service_tier":"flex",
+    ///         "usage":{"prompt_tokens":3,"completion_tokens":1}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::quick("o4-mini", "Summarize the logs").with_service_tier("flex");
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(response.service_tier.as_deref(), Some("flex"));
+    /// assert_eq!(mock.last_request().unwrap().json_body()?["service_tier"], "flex");
+    ///
+    /// let invalid = LLMRequest::quick("o4-mini", "Hi").with_service_tier("cheap");
+    /// assert!(matches!(
+    ///     provider.send_request(&invalid).await,
+    ///     Err(TurbineError::InvalidRequest(_))
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_service_tier(mut self, service_tier: impl Into<String>) -> Self {
+        self.service_tier = Some(service_tier.into());
+        self
+    }
+
+    /// Identifies the end user to Anthropic, sent as `metadata.user_id`.
+    ///
+    /// Anthropic uses it to detect abuse per user instead of per API key. Use an opaque
+    /// value such as a hash or UUID, never a name, email address or phone number. Other
+    /// providers ignore it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":3,"output_tokens":1}}"#,
+    /// )));
+    /// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::quick("claude-3-5-haiku-20241022", "Hello")
+    ///     .with_anthropic_metadata("5f0c9d2e-user-7731");
+    /// provider.send_request(&request).await?;
+    /// let body = mock.last_request().unwrap().json_body()?;
+    /// assert_eq!(body["metadata"]["user_id"], "5f0c9d2e-user-7731");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_anthropic_metadata(mut self, user_id: impl Into<String>) -> Self {
+        self.anthropic_user_id = Some(user_id.into());
+        self
+    }
+
+    /// Sets the order among requests waiting for the client's rate limit.
+    ///
+    /// While [`TurbineClient::with_rate_limit`](crate::TurbineClient::with_rate_limit)
+    /// throttles, waiting requests of higher priority are sent first, so interactive calls
+    /// are not stuck behind background jobs. Without a rate limit it has no effect, and it
+    /// is never sent to the provider.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Priority};
+    ///
+    /// let request = LLMRequest::quick("gpt-4o-mini", "Summarize the backlog")
+    ///     .with_priority(Priority::Low);
+    /// assert_eq!(request.priority, Priority::Low);
+    /// ```
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Moves `<think>...</think>` blocks out of the content into [`LLMResponse::reasoning`].
+    ///
+    /// For R1-style models that write their reasoning into the content instead of a separate
+    /// field. Applies to responses and streams of [`TurbineClient`](crate::TurbineClient);
+    /// streamed deltas only carry the answer (see
+    /// [`ResponseStream::with_extract_thinking`](crate::ResponseStream::with_extract_thinking)).
+    /// It runs before the client's response transform.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::groq::GroqProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"<think>Simple sum.</think>\n\n4"}}],"usage":{"prompt_tokens":5,"completion_tokens":8}}"#,
+    /// )));
+    /// let client = TurbineClient::from_provider(GroqProvider::new_with_key("sk-test").with_transport(mock));
+    ///
+    /// let request = LLMRequest::quick("deepseek-r1-distill-llama-70b", "2 + 2?").with_extract_thinking(true);
+    /// let response = client.send_request(&request).await?;
+    /// assert_eq!(response.content, "4");
+    /// assert_eq!(response.reasoning.as_deref(), Some("Simple sum."));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_extract_thinking(mut self, enabled: bool) -> Self {
+        self.extract_thinking = enabled;
+        self
+    }
+
+    /// Adds a tool the model may call.
+    ///
+    /// See [`crate::tools`] for how tool calls are returned.
+    pub fn with_tool(mut self, tool: Tool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Adds several tools the model may call.
+    pub fn with_tools(mut self, tools: impl IntoIterator<Item = Tool>) -> Self {
+        self.tools.extend(tools);
+        self
+    }
+
+    /// Estimates the number of input tokens: system prompt plus all messages.
+    ///
+    /// See [`crate::tokens`] for how the estimate is computed.
+    pub fn estimate_input_tokens(&self) -> u32 {
+        tokens::estimate_request_tokens(self)
+    }
+
+    /// Returns whether the estimated input plus `max_tokens` fits the model's context window.
+    ///
+    /// Returns `None` when the model's context window is unknown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Hello!"));
+    /// assert_eq!(request.fits_context(), Some(true));
+    ///
+    /// let huge = LLMRequest::new("gpt-4o-mini").with_message(Message::user("a".repeat(600_000)));
+    /// assert_eq!(huge.fits_context(), Some(false));
+    ///
+    /// assert_eq!(LLMRequest::new("my-custom-model").fits_context(), None);
+    /// ```
+    pub fn fits_context(&self) -> Option<bool> {
+        let window = catalog::context_window(&self.model)?;
+        Some(self.required_context() <= window)
+    }
+
+    /// Returns how many tokens of the model's context window remain after the estimated
+    /// input and `max_tokens`, saturating at zero.
+    ///
+    /// Returns `None` when the model's context window is unknown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// let request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_message(Message::user("Hello!"))
+    ///     .with_max_tokens(1000);
+    /// assert_eq!(request.remaining_context(), Some(128_000 - 1000 - 6));
+    ///
+    /// let unbounded = request.with_max_tokens(u32::MAX);
+    /// assert_eq!(unbounded.remaining_context(), Some(0));
+    /// ```
+    pub fn remaining_context(&self) -> Option<u32> {
+        let window = catalog::context_window(&self.model)?;
+        Some(window.saturating_sub(self.required_context()))
+    }
+
+    /// Drops old messages until the request fits the model's context window.
+    ///
+    /// Messages are removed oldest first until the estimated input plus `max_tokens` fits.
+    /// The system prompt and system-role messages are never dropped, and the most recent
+    /// messages protected by the strategy are always kept, even if the request still does
+    /// not fit. An assistant message left at the start of the history is dropped too, so the
+    /// conversation keeps starting with a user turn.
+    ///
+    /// Returns the number of messages removed, or `None` if the model's context window is
+    /// unknown (in which case the request is left untouched).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{LLMRequest, Message, TruncationStrategy};
+    ///
+    /// let long_turn = "a".repeat(200_000);
+    /// let mut request = LLMRequest::new("gpt-4o-mini")
+    ///     .with_system_prompt("You are a helpful assistant.")
+    ///     .with_messages(vec![
+    ///         Message::user(long_turn.clone()),
+    ///         Message::assistant(long_turn.clone()),
+    ///         Message::user(long_turn),
+    ///         Message::assistant("Short answer."),
+    ///         Message::user("And now?"),
+    ///     ]);
+    /// assert_eq!(request.fits_context(), Some(false));
+    ///
+    /// let removed = request.truncate_to_fit(TruncationStrategy::DropOldest);
+    /// assert_eq!(removed, Some(2));
+    /// assert_eq!(request.messages.len(), 3);
+    /// assert_eq!(request.fits_context(), Some(true));
+    /// assert!(request.system_prompt.is_some());
+    /// ```
+    pub fn truncate_to_fit(&mut self, strategy: TruncationStrategy) -> Option<usize> {
+        let window = catalog::context_window(&self.model)?;
+        let keep_recent = match strategy {
+            TruncationStrategy::DropOldest => 1,
+            TruncationStrategy::KeepSystemAndRecent(n) => n,
+        };
+
+        let mut removed = 0;
+        loop {
+            let fits = self.required_context() <= window;
+            // A tool result cannot outlive the assistant turn that requested it
+            let leading_reply = self
+                .messages
+                .iter()
+                .find(|m| m.role != "system")
+                .is_some_and(|m| m.role == "assistant" || m.role == "tool");
+            if fits && !leading_reply {
+                break;
+            }
+
+            let droppable = self.messages.iter().filter(|m| m.role != "system").count();
+            if droppable <= keep_recent {
+                break;
+            }
+
+            let oldest = self.messages.iter().position(|m| m.role != "system")?;
+            self.messages.remove(oldest);
+            removed += 1;
+        }
+
+        Some(removed)
+    }
+
+    fn required_context(&self) -> u32 {
+        self.estimate_input_tokens()
+            .saturating_add(self.effective_max_tokens())
+    }
+}
+
+/// Builds a request from `(model, text)` with a single user message.
+impl From<(&str, &str)> for LLMRequest {
+    fn from((model, text): (&str, &str)) -> Self {
+        Self::quick(model, text)
+    }
+}
+
+/// How [`LLMRequest::truncate_to_fit`] chooses which messages to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    /// Drop the oldest messages first, always keeping the latest message
+    DropOldest,
+    /// Drop the oldest messages first, always keeping system messages and the `n` most
+    /// recent non-system messages
+    KeepSystemAndRecent(usize),
+}
+
+/// Timing of a response.
+///
+/// Throughput is measured over the generation window: the network duration of the final
+/// attempt for regular requests, and from the first token to the end of the stream for
+/// streamed ones, so the time spent processing the prompt does not count.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::openai::OpenAIProvider;
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{LLMRequest, TurbineClient};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let sse = concat!(
+///     "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"},\"finish_reason\":\"stop\"}]}\n\n",
+///     "data: [DONE]\n\n",
+/// );
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(200, sse)));
+/// let client =
+///     TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(mock));
+///
+/// let stream = client.send_request_stream(&LLMRequest::quick("gpt-4o-mini", "Hello")).await?;
+/// let response = stream.into_response().await?;
+/// let metrics = response.metrics.unwrap();
+/// assert!(metrics.time_to_first_token.is_some());
+/// assert!(metrics.duration.is_some());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseMetrics {
+    /// Time from sending the request to the first non-empty text delta; `None` if no text
+    /// was generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_to_first_token: Option<Duration>,
+    /// Time from sending the request to receiving the complete response, or to the end of
+    /// the stream; retries and backoff are not included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+    /// Output tokens per second over the generation window; `None` if the provider reported
+    /// no output tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_second: Option<f64>,
+    /// Time the request waited for the client's rate limit before it was sent; `None` if
+    /// it was sent immediately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_delay: Option<Duration>,
+}
+
+impl ResponseMetrics {
+    /// Computes throughput of `output_tokens` generated over `window`.
+    pub(crate) fn throughput(output_tokens: u32, window: Duration) -> Option<f64> {
+        let seconds = window.as_secs_f64();
+        (output_tokens > 0 && seconds > 0.0).then(|| f64::from(output_tokens) / seconds)
+    }
 }
 
 /// Token usage information for a request/response.
 ///
-/// Tracks the number of tokens consumed by the input prompt and generated output.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Tracks the number of tokens consumed by the input prompt and generated output, and the
+/// cached and reasoning tokens among them when the provider reports them.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::LLMRequest;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"choices":[{"message":{"content":"42"}}],"usage":{"prompt_tokens":2048,"completion_tokens":300,
+///         "prompt_tokens_details":{"cached_tokens":1920},"completion_tokens_details":{"reasoning_tokens":256}}}"#,
+/// )));
+/// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+///
+/// let response = provider.send_request(&LLMRequest::quick("o3-mini", "...")).await?;
+/// assert_eq!(response.usage.input_tokens, 2048);
+/// assert_eq!(response.usage.cached_input_tokens, Some(1920));
+/// assert_eq!(response.usage.reasoning_tokens, Some(256));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Usage {
     /// Number of tokens in the input prompt
     pub input_tokens: u32,
     /// Number of tokens in the generated output
     pub output_tokens: u32,
+    /// Input tokens read from the provider's prompt cache, which are billed at a lower rate
+    ///
+    /// OpenAI and Gemini include them in `input_tokens`; Anthropic does not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_tokens: Option<u32>,
+    /// Output tokens spent on internal reasoning, included in `output_tokens`
+    /// (OpenAI reasoning models, Gemini thinking models)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u32>,
+}
+
+impl Usage {
+    /// Returns the cost of the input tokens in US dollars at `model`'s list price, or `None`
+    /// if the model is unknown (see [`catalog::price`]).
+    ///
+    /// Cached input tokens are counted at the full rate, so this is an upper bound when a
+    /// prompt cache was hit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::Usage;
+    ///
+    /// let usage = Usage { input_tokens: 1_000_000, output_tokens: 200_000, ..Default::default() };
+    /// assert_eq!(usage.input_cost("gpt-4o"), Some(2.5));
+    /// assert_eq!(usage.output_cost("gpt-4o"), Some(2.0));
+    /// assert_eq!(usage.estimated_cost("gpt-4o"), Some(4.5));
+    /// assert_eq!(usage.estimated_cost("my-fine-tuned-model"), None);
+    /// ```
+    pub fn input_cost(&self, model: &str) -> Option<f64> {
+        let price = catalog::price(model)?;
+        Some(f64::from(self.input_tokens) * price.input_per_million / 1_000_000.0)
+    }
+
+    /// Returns the cost of the output tokens, including reasoning tokens, in US dollars at
+    /// `model`'s list price, or `None` if the model is unknown.
+    pub fn output_cost(&self, model: &str) -> Option<f64> {
+        let price = catalog::price(model)?;
+        Some(f64::from(self.output_tokens) * price.output_per_million / 1_000_000.0)
+    }
+
+    /// Returns the combined input and output cost in US dollars, or `None` if the model is
+    /// unknown.
+    pub fn estimated_cost(&self, model: &str) -> Option<f64> {
+        Some(self.input_cost(model)? + self.output_cost(model)?)
+    }
+}
+
+/// Adds the counts of another response, e.g. to total a session.
+///
+/// Optional counts stay `None` only while neither side reports them.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::Usage;
+///
+/// let mut total = Usage { input_tokens: 10, output_tokens: 5, ..Default::default() };
+/// total += &Usage { input_tokens: 20, output_tokens: 7, cached_input_tokens: Some(8), ..Default::default() };
+/// assert_eq!(total.input_tokens, 30);
+/// assert_eq!(total.output_tokens, 12);
+/// assert_eq!(total.cached_input_tokens, Some(8));
+/// assert_eq!(total.reasoning_tokens, None);
+/// ```
+impl std::ops::AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, other: &Usage) {
+        fn add(total: Option<u32>, other: Option<u32>) -> Option<u32> {
+            match (total, other) {
+                (None, None) => None,
+                _ => Some(total.unwrap_or(0) + other.unwrap_or(0)),
+            }
+        }
+
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cached_input_tokens = add(self.cached_input_tokens, other.cached_input_tokens);
+        self.reasoning_tokens = add(self.reasoning_tokens, other.reasoning_tokens);
+    }
+}
+
+/// A source citation attached to a response.
+///
+/// Anthropic returns citations for documents sent with citations enabled (see
+/// [`DocumentSource::with_citations`](crate::DocumentSource::with_citations)); Gemini returns
+/// them when it recites from its sources. Fields a provider does not report are `None`.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{LLMRequest, Message};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"content":[
+///         {"type":"text","text":"According to the report, "},
+///         {"type":"text","text":"revenue grew 12%.","citations":[{
+///             "type":"page_location","cited_text":"Revenue grew 12% year over year.",
+///             "document_index":0,"document_title":"Annual Report",
+///             "start_page_number":3,"end_page_number":4}]}],
+///         "usage":{"input_tokens":120,"output_tokens":12}}"#,
+/// )));
+/// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock);
+/// let request = LLMRequest::new("claude-3-5-sonnet-20241022")
+///     .with_message(Message::user("How did revenue change?"));
+///
+/// let response = provider.send_request(&request).await?;
+/// assert_eq!(response.content, "According to the report, revenue grew 12%.");
+/// let citations = response.citations.unwrap();
+/// assert_eq!(citations[0].title.as_deref(), Some("Annual Report"));
+/// assert_eq!(citations[0].start_index, Some(3));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// The quoted source text
+    pub cited_text: Option<String>,
+    /// Title of the cited source
+    pub title: Option<String>,
+    /// URI of the cited source
+    pub uri: Option<String>,
+    /// Index of the cited document among the documents in the request (Anthropic)
+    pub document_index: Option<u32>,
+    /// Start of the cited range: a character, page or block index into the cited document
+    /// for Anthropic, a character index into the response content for Gemini
+    pub start_index: Option<u32>,
+    /// End of the cited range, using the same unit as `start_index`
+    pub end_index: Option<u32>,
 }
 
 /// Response from an LLM provider.
@@ -293,6 +1445,139 @@ pub struct LLMResponse {
     pub content: String,
     /// Token usage statistics
     pub usage: Usage,
+    /// Why the model stopped generating, when the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// Source citations, for providers that return them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<Vec<Citation>>,
+    /// Tool calls requested by the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// All alternative responses, when several were generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<Choice>>,
+    /// The model's explanation when it declined to answer (OpenAI); `content` is then empty
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":null,"refusal":"I can't help with that."},"finish_reason":"stop"}],
+    ///         "usage":{"prompt_tokens":12,"completion_tokens":7}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+    ///
+    /// let response = provider.send_request(&LLMRequest::quick("gpt-4o", "...")).await?;
+    /// assert_eq!(response.content, "");
+    /// assert_eq!(response.refusal.as_deref(), Some("I can't help with that."));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<String>,
+    /// The processing tier that served the request (OpenAI)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+    /// Identifies the backend configuration that served the request (OpenAI and Groq
+    /// `system_fingerprint`)
+    ///
+    /// Responses with the same fingerprint were produced by the same configuration, which
+    /// helps to tell whether a change in output with a fixed seed came from the backend. It
+    /// is `None` for providers that do not report it and for streamed responses.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"system_fingerprint":"fp_44709d6fcb","choices":[{"message":{"content":"Hi!"}}],
+    ///         "usage":{"prompt_tokens":3,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+    ///
+    /// let response = provider.send_request(&LLMRequest::quick("gpt-4o-mini", "Hello")).await?;
+    /// assert_eq!(response.system_fingerprint.as_deref(), Some("fp_44709d6fcb"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+    /// Reasoning extracted from `<think>` blocks when
+    /// [`LLMRequest::with_extract_thinking`] is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Notable things that happened to the request without failing it, such as settings
+    /// the provider ignored or a JSON repair; empty in the common case
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, groq::GroqProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"A fox"}}],"usage":{"prompt_tokens":6,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = GroqProvider::new_with_key("gsk-test").with_transport(mock);
+    ///
+    /// let request = LLMRequest::quick("llama-3.1-8b-instant", "Name an animal").with_candidate_count(3);
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(
+    ///     response.warnings,
+    ///     ["candidate_count is not supported by Groq; one response was generated"]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// The model version that served the request, as reported by the provider
+    ///
+    /// This may differ from the requested model when an alias is resolved. When the provider
+    /// does not report it, it is the requested model. Streamed responses take it from the
+    /// provider's stream events.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"model":"claude-3-5-sonnet-20241022","content":[{"type":"text","text":"Hi!"}],
+    ///         "usage":{"input_tokens":3,"output_tokens":2}}"#,
+    /// )));
+    /// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock);
+    ///
+    /// let response = provider.send_request(&LLMRequest::quick("claude-3-5-sonnet-latest", "Hello")).await?;
+    /// assert_eq!(response.model, "claude-3-5-sonnet-20241022");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub model: String,
+    /// Timing measurements, set for responses returned by a [`TurbineClient`](crate::TurbineClient)
+    /// or collected from a stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<ResponseMetrics>,
 }
 
 impl LLMResponse {
@@ -303,7 +1588,106 @@ impl LLMResponse {
             usage: Usage {
                 input_tokens,
                 output_tokens,
+                ..Default::default()
             },
+            finish_reason: None,
+            citations: None,
+            tool_calls: None,
+            choices: None,
+            refusal: None,
+            service_tier: None,
+            system_fingerprint: None,
+            reasoning: None,
+            warnings: Vec::new(),
+            model: String::new(),
+            metrics: None,
         }
     }
+
+    /// Returns whether the output was cut off by the token limit.
+    ///
+    /// True for [`FinishReason::Length`]: OpenAI and Groq `length`, Anthropic `max_tokens`,
+    /// Gemini `MAX_TOKENS`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{FinishReason, LLMResponse};
+    ///
+    /// let mut response = LLMResponse::new("The answer is".to_string(), 5, 3);
+    /// response.finish_reason = Some(FinishReason::Length);
+    /// assert!(response.was_truncated());
+    /// assert!(!response.stopped_naturally());
+    /// ```
+    pub fn was_truncated(&self) -> bool {
+        self.finish_reason == Some(FinishReason::Length)
+    }
+
+    /// Returns whether the provider blocked or filtered the output, or the model refused.
+    ///
+    /// True for [`FinishReason::ContentFilter`] (OpenAI and Groq `content_filter`,
+    /// Anthropic `refusal`, Gemini `SAFETY`, `RECITATION`, `BLOCKLIST`,
+    /// `PROHIBITED_CONTENT` and `SPII`) and when [`refusal`](Self::refusal) is set.
+    pub fn was_filtered(&self) -> bool {
+        self.finish_reason == Some(FinishReason::ContentFilter) || self.refusal.is_some()
+    }
+
+    /// Returns whether the model finished its answer on its own.
+    ///
+    /// True for [`FinishReason::Stop`]: OpenAI and Groq `stop`, Anthropic `end_turn` and
+    /// `stop_sequence`, Gemini `STOP`.
+    pub fn stopped_naturally(&self) -> bool {
+        self.finish_reason == Some(FinishReason::Stop)
+    }
+
+    /// Returns whether the model asked to call tools.
+    ///
+    /// True for [`FinishReason::ToolCalls`] (OpenAI and Groq `tool_calls`, Anthropic
+    /// `tool_use`, Gemini responses with function calls) and whenever
+    /// [`tool_calls`](Self::tool_calls) is not empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{FinishReason, LLMResponse};
+    ///
+    /// let mut response = LLMResponse::new(String::new(), 5, 3);
+    /// response.finish_reason = Some(FinishReason::ToolCalls);
+    /// assert!(response.made_tool_call());
+    /// assert!(!response.was_truncated());
+    /// ```
+    pub fn made_tool_call(&self) -> bool {
+        self.finish_reason == Some(FinishReason::ToolCalls)
+            || self
+                .tool_calls
+                .as_ref()
+                .is_some_and(|calls| !calls.is_empty())
+    }
+}
+
+/// Displays the response content.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::LLMResponse;
+///
+/// let response = LLMResponse::new("Hello!".to_string(), 5, 2);
+/// assert_eq!(format!("{}", response), "Hello!");
+/// println!("{}", response);
+/// ```
+impl std::fmt::Display for LLMResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.content)
+    }
+}
+
+/// One of several alternative responses to the same request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Choice {
+    /// The generated text content
+    pub content: String,
+    /// Why the model stopped generating this alternative
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
 }
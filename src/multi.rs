@@ -0,0 +1,214 @@
+//! A client that routes requests to several providers.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{
+    client::{TurbineClient, provider_impl},
+    error::Result,
+    models::{LLMRequest, LLMResponse},
+    streaming::ResponseStream,
+    transport::{HttpTransport, ReqwestTransport},
+    types::Provider,
+};
+
+/// Routes each request to the provider its model belongs to.
+///
+/// The provider is chosen with [`Provider::from_model_string`], so `request.model` can be
+/// `"provider/model"` or a model name the provider can be inferred from. A provider prefix is
+/// stripped before the request is sent.
+///
+/// Requests go through a [`TurbineClient`] per provider, so its retry policy, guards, rate
+/// limit, audit log and defaults apply. Clients are created on first use and reused; their
+/// API keys come from [`with_api_key`](Self::with_api_key) or the provider's environment
+/// variable, and [`with_client_config`](Self::with_client_config) sets them up. All created
+/// clients share one HTTP connection pool. [`with_client`](Self::with_client) supplies a
+/// ready-made client for a provider instead.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::multi::MultiClient;
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{LLMRequest, Provider};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(
+///     MockTransport::new()
+///         .with_response(HttpResponse::new(
+///             200,
+///             r#"{"choices":[{"message":{"content":"From OpenAI"}}],"usage":{"prompt_tokens":3,"completion_tokens":2}}"#,
+///         ))
+///         .with_response(HttpResponse::new(
+///             200,
+///             r#"{"content":[{"type":"text","text":"From Anthropic"}],"usage":{"input_tokens":3,"output_tokens":2}}"#,
+///         )),
+/// );
+/// let client = MultiClient::new()
+///     .with_api_key(Provider::OpenAI, "sk-openai")
+///     .with_api_key(Provider::Anthropic, "sk-anthropic")
+///     .with_transport(mock.clone());
+///
+/// let openai = client.send_request(&LLMRequest::quick("openai/gpt-4o-mini", "Hi")).await?;
+/// let anthropic = client.send_request(&LLMRequest::quick("claude-3-5-haiku-20241022", "Hi")).await?;
+/// assert_eq!(openai.content, "From OpenAI");
+/// assert_eq!(anthropic.content, "From Anthropic");
+///
+/// let sent = mock.requests();
+/// assert!(sent[0].url.starts_with("https://api.openai.com"));
+/// assert_eq!(sent[0].json_body()?["model"], "gpt-4o-mini");
+/// assert!(sent[1].url.starts_with("https://api.anthropic.com"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiClient {
+    api_keys: HashMap<Provider, String>,
+    transport: Arc<dyn HttpTransport>,
+    client_config: Option<ClientConfig>,
+    clients: Mutex<HashMap<Provider, TurbineClient>>,
+}
+
+/// A function that sets up each client a [`MultiClient`] creates, see
+/// [`MultiClient::with_client_config`].
+pub type ClientConfig = Arc<dyn Fn(TurbineClient) -> TurbineClient + Send + Sync>;
+
+impl MultiClient {
+    /// Creates a client that reads API keys from the environment.
+    pub fn new() -> Self {
+        Self {
+            api_keys: HashMap::new(),
+            transport: Arc::new(ReqwestTransport::new()),
+            client_config: None,
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the API key for `provider` instead of reading its environment variable.
+    pub fn with_api_key(mut self, provider: Provider, api_key: impl Into<String>) -> Self {
+        self.api_keys.insert(provider, api_key.into());
+        self
+    }
+
+    /// Replaces the HTTP transport shared by all providers.
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Runs `config` on every client created on first use, e.g. to set a retry policy,
+    /// guards or an audit log for all providers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use turbine_llm::multi::MultiClient;
+    /// use turbine_llm::retry::RetryPolicy;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Provider};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(529, r#"{"type":"error","error":{"type":"overloaded_error"}}"#))
+    ///         .with_response(HttpResponse::new(
+    ///             200,
+    ///             r#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":3,"output_tokens":1}}"#,
+    ///         )),
+    /// );
+    /// let client = MultiClient::new()
+    ///     .with_api_key(Provider::Anthropic, "sk-test")
+    ///     .with_transport(mock.clone())
+    ///     .with_client_config(Arc::new(|client| {
+    ///         client.with_retry_policy(RetryPolicy::new(2).with_initial_backoff(Duration::from_millis(1)))
+    ///     }));
+    ///
+    /// let response = client.send_request(&LLMRequest::quick("claude-3-5-haiku-20241022", "Hi")).await?;
+    /// assert_eq!(response.content, "Hi");
+    /// assert_eq!(mock.requests().len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_client_config(mut self, config: ClientConfig) -> Self {
+        self.client_config = Some(config);
+        self
+    }
+
+    /// Sends requests for `provider` through `client` instead of creating one.
+    pub fn with_client(mut self, provider: Provider, client: TurbineClient) -> Self {
+        self.clients
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(provider, client);
+        self
+    }
+
+    /// Sends a request to the provider of its model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be determined from the model, its API key is
+    /// missing, or the request fails.
+    pub async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let (client, request) = self.route(request)?;
+        client.send_request(&request).await
+    }
+
+    /// Sends a request to the provider of its model and streams the response.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`send_request`](Self::send_request).
+    pub async fn send_request_stream(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let (client, request) = self.route(request)?;
+        client.send_request_stream(&request).await
+    }
+
+    /// Picks the client for `request` and strips the provider prefix from its model.
+    fn route(&self, request: &LLMRequest) -> Result<(TurbineClient, LLMRequest)> {
+        let (kind, model) = Provider::from_model_string(&request.model)?;
+        let client = self.client(kind)?;
+
+        let mut request = request.clone();
+        request.model = model;
+        Ok((client, request))
+    }
+
+    /// Returns the cached client of `kind`, creating it on first use.
+    fn client(&self, kind: Provider) -> Result<TurbineClient> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&kind) {
+            return Ok(client.clone());
+        }
+
+        let api_key = match self.api_keys.get(&kind) {
+            Some(api_key) => api_key.clone(),
+            None => kind.api_key_from_env()?,
+        };
+        let provider = provider_impl(
+            kind,
+            api_key,
+            None,
+            self.transport.clone(),
+            None,
+            None,
+            None,
+        );
+        let mut client = TurbineClient::from_parts(provider, None);
+        if let Some(config) = &self.client_config {
+            client = config(client);
+        }
+        clients.insert(kind, client.clone());
+        Ok(client)
+    }
+}
+
+impl Default for MultiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,180 @@
+//! Composing prompts from reusable fragments and templates.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::error::{Result, TurbineError};
+
+/// Assembles a system prompt from titled sections and plain fragments.
+///
+/// Each section is rendered as a Markdown heading followed by its body, and fragments are
+/// separated by a blank line, so prompts built from shared pieces are laid out the same way
+/// everywhere. Blank fragments are skipped.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::prompt::PromptBuilder;
+/// use turbine_llm::LLMRequest;
+///
+/// let formatting = "Answer in at most three sentences.";
+/// let prompt = PromptBuilder::new()
+///     .add_text("You are a support assistant for Acme.")
+///     .add_section("Formatting", formatting)
+///     .add_section("Safety", "Never share account credentials.")
+///     .render();
+///
+/// let expected = [
+///     "You are a support assistant for Acme.",
+///     "## Formatting\nAnswer in at most three sentences.",
+///     "## Safety\nNever share account credentials.",
+/// ];
+/// assert_eq!(prompt, expected.join("\n\n"));
+///
+/// let request = LLMRequest::new("gpt-4o-mini").with_system_prompt(prompt);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptBuilder {
+    fragments: Vec<String>,
+}
+
+impl PromptBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a section with a heading.
+    pub fn add_section(mut self, title: impl AsRef<str>, body: impl AsRef<str>) -> Self {
+        let body = body.as_ref().trim();
+        if !body.is_empty() {
+            self.fragments
+                .push(format!("## {}\n{}", title.as_ref().trim(), body));
+        }
+        self
+    }
+
+    /// Adds a fragment without a heading.
+    pub fn add_text(mut self, text: impl AsRef<str>) -> Self {
+        let text = text.as_ref().trim();
+        if !text.is_empty() {
+            self.fragments.push(text.to_string());
+        }
+        self
+    }
+
+    /// Returns the assembled prompt.
+    pub fn render(&self) -> String {
+        self.fragments.join("\n\n")
+    }
+}
+
+/// A prompt with `{{name}}` placeholders.
+///
+/// Whitespace inside the braces is ignored, so `{{ name }}` works too. Text that is not a
+/// complete placeholder is kept as is.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use turbine_llm::prompt::Template;
+/// use turbine_llm::{Message, TurbineError};
+///
+/// let template = Template::new("Summarize {{text}} in {{ n }} words");
+/// assert_eq!(template.variables(), ["text", "n"]);
+///
+/// let vars = HashMap::from([("text", "the report".to_string()), ("n", 50.to_string())]);
+/// let message = Message::user(template.render(&vars)?);
+/// assert_eq!(message.content, "Summarize the report in 50 words");
+///
+/// let missing = HashMap::from([("text", "the report")]);
+/// assert!(matches!(template.render(&missing), Err(TurbineError::MissingField(_))));
+/// # Ok::<(), TurbineError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+}
+
+impl Template {
+    /// Creates a template from text with `{{name}}` placeholders.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    /// Returns the placeholder names in order of appearance, including repeats.
+    pub fn variables(&self) -> Vec<&str> {
+        self.segments()
+            .filter_map(|segment| match segment {
+                Segment::Variable(name) => Some(name),
+                Segment::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Replaces every placeholder with its value from `vars`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::MissingField`] naming the first placeholder without a value.
+    pub fn render<K, V>(&self, vars: &HashMap<K, V>) -> Result<String>
+    where
+        K: Borrow<str> + Eq + Hash,
+        V: Display,
+    {
+        let mut rendered = String::with_capacity(self.source.len());
+        for segment in self.segments() {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Variable(name) => {
+                    let value = vars.get(name).ok_or_else(|| {
+                        TurbineError::MissingField(format!("template variable `{}`", name))
+                    })?;
+                    rendered.push_str(&value.to_string());
+                }
+            }
+        }
+        Ok(rendered)
+    }
+
+    fn segments(&self) -> impl Iterator<Item = Segment<'_>> {
+        let mut rest = self.source.as_str();
+        std::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let Some(start) = rest.find("{{") else {
+                return Some(Segment::Text(std::mem::take(&mut rest)));
+            };
+            if start > 0 {
+                let (text, tail) = rest.split_at(start);
+                rest = tail;
+                return Some(Segment::Text(text));
+            }
+            match rest[2..].find("}}") {
+                Some(end) if !rest[2..2 + end].trim().is_empty() => {
+                    let name = rest[2..2 + end].trim();
+                    rest = &rest[2 + end + 2..];
+                    Some(Segment::Variable(name))
+                }
+                // An empty or unterminated placeholder is plain text
+                _ => {
+                    let (text, tail) = rest.split_at(2);
+                    rest = tail;
+                    Some(Segment::Text(text))
+                }
+            }
+        })
+    }
+}
+
+/// A piece of a [`Template`].
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
@@ -1,40 +1,181 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::{
+    content::ContentPart,
     error::{Result, TurbineError},
-    models::{LLMRequest, LLMResponse, Message},
-    types::{OutputFormat, Provider},
+    health::{self, HealthStatus},
+    models::{Citation, LLMRequest, LLMResponse, Message, Usage},
+    streaming::{RawEventStream, ResponseStream, StreamChunk},
+    tools::{Tool, ToolCall},
+    transport::{HttpRequest, HttpTransport, ReqwestTransport},
+    types::{Provider, api_key_from_env_var},
 };
 
-use super::LLMProviderTrait;
+use super::{
+    ApiKey, KeyProvider, LLMProviderTrait, map_finish_reason, normalize_request, request_warnings,
+    send_json, send_stream,
+    sse::{self, EventStream, SseEvent},
+};
+
+/// The `anthropic-version` header sent unless overridden.
+pub const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
 
 pub struct AnthropicProvider {
-    api_key: String,
+    api_key: ApiKey,
     base_url: String,
+    version: String,
+    betas: Vec<String>,
+    transport: Arc<dyn HttpTransport>,
+    max_request_bytes: Option<usize>,
 }
 
 impl AnthropicProvider {
     pub fn new() -> Result<Self> {
-        let api_key = std::env::var(Provider::Anthropic.env_var())?;
-        Ok(Self {
-            api_key,
-            base_url: Provider::Anthropic.base_url().to_string(),
-        })
+        let api_key = Provider::Anthropic.api_key_from_env()?;
+        Ok(Self::new_with_key(api_key))
+    }
+
+    /// Creates a provider with the API key read from the environment variable `name`
+    /// instead of `ANTHROPIC_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if `name` is not set.
+    pub fn from_env_var(name: &str) -> Result<Self> {
+        Ok(Self::new_with_key(api_key_from_env_var(name)?))
     }
 
     pub fn new_with_key(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: ApiKey::Static(api_key.into()),
             base_url: Provider::Anthropic.base_url().to_string(),
+            version: DEFAULT_ANTHROPIC_VERSION.to_string(),
+            betas: Vec::new(),
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         }
     }
+
+    /// Overrides the `anthropic-version` header (default: [`DEFAULT_ANTHROPIC_VERSION`]).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use turbine_llm::TurbineClient;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    ///
+    /// let provider = AnthropicProvider::new()?
+    ///     .with_anthropic_version("2023-06-01")
+    ///     .with_anthropic_beta("prompt-caching-2024-07-31");
+    /// let client = TurbineClient::from_provider(provider);
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn with_anthropic_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Enables a beta feature by adding it to the `anthropic-beta` header.
+    ///
+    /// Can be called several times; all features are sent comma-separated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"Hi!"}],"usage":{"input_tokens":5,"output_tokens":2}}"#,
+    /// )));
+    /// let provider = AnthropicProvider::new_with_key("sk-test")
+    ///     .with_anthropic_beta("feature-a")
+    ///     .with_anthropic_beta("feature-b")
+    ///     .with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("claude-3-5-haiku-20241022").with_message(Message::user("Hello"));
+    /// provider.send_request(&request).await?;
+    ///
+    /// let sent = mock.last_request().unwrap();
+    /// assert_eq!(sent.header("anthropic-version"), Some("2023-06-01"));
+    /// assert_eq!(sent.header("anthropic-beta"), Some("feature-a,feature-b"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_anthropic_beta(mut self, feature: impl Into<String>) -> Self {
+        self.betas.push(feature.into());
+        self
+    }
+
+    /// Fetches the API key from `key_provider` before every request instead of using a
+    /// fixed key.
+    pub fn with_key_provider(mut self, key_provider: KeyProvider) -> Self {
+        self.api_key = ApiKey::Dynamic(key_provider);
+        self
+    }
+
+    /// Sends requests to `base_url` instead of `https://api.anthropic.com/v1`, e.g. a local
+    /// test server or a proxy. Paths such as `/messages` are appended to it.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Rejects request bodies larger than `max` bytes with
+    /// [`TurbineError::RequestTooLarge`] instead of sending them, whatever the transport.
+    /// No limit by default.
+    pub fn with_max_request_bytes(mut self, max: usize) -> Self {
+        self.max_request_bytes = Some(max);
+        self
+    }
+
+    /// Replaces the HTTP transport used to reach the API.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"content":[{"type":"text","text":"Hi!"}],"usage":{"input_tokens":5,"output_tokens":2}}"#,
+    /// )));
+    /// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("claude-3-5-haiku-20241022")
+    ///     .with_system_prompt("Be brief.")
+    ///     .with_message(Message::user("Hello"));
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(response.content, "Hi!");
+    ///
+    /// let sent = mock.last_request().unwrap();
+    /// assert_eq!(sent.header("x-api-key"), Some("sk-test"));
+    /// assert_eq!(sent.json_body()?["system"], "Be brief.");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
 }
 
 #[derive(Serialize)]
 struct AnthropicRequestBody {
     model: String,
-    messages: Vec<Message>,
+    messages: Vec<AnthropicMessage>,
     max_tokens: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
@@ -42,34 +183,337 @@ struct AnthropicRequestBody {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<Metadata>,
+}
+
+#[derive(Serialize)]
+struct Metadata {
+    user_id: String,
+}
+
+#[derive(Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+}
+
+impl From<&Tool> for AnthropicTool {
+    fn from(tool: &Tool) -> Self {
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            input_schema: tool.parameters.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AnthropicMessage {
+    role: String,
+    content: AnthropicContent,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum AnthropicContent {
+    Text(String),
+    Blocks(Vec<InputContentBlock>),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InputContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: Base64Source,
+    },
+    Document {
+        source: Base64Source,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        citations: Option<CitationsConfig>,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: AnthropicContent,
+    },
+}
+
+#[derive(Serialize)]
+struct CitationsConfig {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct Base64Source {
+    #[serde(rename = "type")]
+    source_type: &'static str,
+    media_type: String,
+    data: String,
+}
+
+impl Base64Source {
+    fn new(media_type: String, data: String) -> Self {
+        Self {
+            source_type: "base64",
+            media_type,
+            data,
+        }
+    }
+}
+
+impl From<ContentPart> for InputContentBlock {
+    fn from(part: ContentPart) -> Self {
+        match part {
+            ContentPart::Image(image) => InputContentBlock::Image {
+                source: Base64Source::new(image.media_type, image.data),
+            },
+            ContentPart::Document(document) => InputContentBlock::Document {
+                citations: document
+                    .citations
+                    .then_some(CitationsConfig { enabled: true }),
+                source: Base64Source::new(document.media_type, document.data),
+            },
+        }
+    }
+}
+
+impl From<Message> for AnthropicMessage {
+    fn from(message: Message) -> Self {
+        // Tool results are sent back as user turns; attached images and documents go
+        // inside the result, after its text
+        if let Some(tool_use_id) = message.tool_call_id {
+            let content = if message.parts.is_empty() {
+                AnthropicContent::Text(message.content)
+            } else {
+                let mut blocks = Vec::new();
+                if !message.content.is_empty() {
+                    blocks.push(InputContentBlock::Text {
+                        text: message.content,
+                    });
+                }
+                blocks.extend(message.parts.into_iter().map(InputContentBlock::from));
+                AnthropicContent::Blocks(blocks)
+            };
+            return Self {
+                role: "user".to_string(),
+                content: AnthropicContent::Blocks(vec![InputContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                }]),
+            };
+        }
+
+        if message.parts.is_empty() && message.tool_calls.is_empty() {
+            return Self {
+                role: message.role,
+                content: AnthropicContent::Text(message.content),
+            };
+        }
+
+        // Anthropic recommends placing images and documents before the text
+        let mut blocks: Vec<InputContentBlock> = message
+            .parts
+            .into_iter()
+            .map(InputContentBlock::from)
+            .collect();
+        if !message.content.is_empty() {
+            blocks.push(InputContentBlock::Text {
+                text: message.content,
+            });
+        }
+        blocks.extend(
+            message
+                .tool_calls
+                .into_iter()
+                .map(|call| InputContentBlock::ToolUse {
+                    id: call.id,
+                    name: call.name,
+                    input: call.arguments,
+                }),
+        );
+
+        Self {
+            role: message.role,
+            content: AnthropicContent::Blocks(blocks),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct AnthropicResponse {
+    model: Option<String>,
     content: Vec<ContentBlock>,
     usage: UsageInfo,
+    stop_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct ContentBlock {
+    #[serde(rename = "type")]
+    block_type: String,
+    #[serde(default)]
     text: String,
+    #[serde(default)]
+    citations: Option<Vec<AnthropicCitation>>,
+    /// Set on `tool_use` blocks
+    id: Option<String>,
+    name: Option<String>,
+    input: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicCitation {
+    cited_text: Option<String>,
+    document_index: Option<u32>,
+    document_title: Option<String>,
+    url: Option<String>,
+    title: Option<String>,
+    start_char_index: Option<u32>,
+    end_char_index: Option<u32>,
+    start_page_number: Option<u32>,
+    end_page_number: Option<u32>,
+    start_block_index: Option<u32>,
+    end_block_index: Option<u32>,
+}
+
+impl From<AnthropicCitation> for Citation {
+    fn from(citation: AnthropicCitation) -> Self {
+        Self {
+            cited_text: citation.cited_text,
+            title: citation.document_title.or(citation.title),
+            uri: citation.url,
+            document_index: citation.document_index,
+            start_index: citation
+                .start_char_index
+                .or(citation.start_page_number)
+                .or(citation.start_block_index),
+            end_index: citation
+                .end_char_index
+                .or(citation.end_page_number)
+                .or(citation.end_block_index),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct UsageInfo {
     input_tokens: u32,
     output_tokens: u32,
+    cache_read_input_tokens: Option<u32>,
 }
 
-#[async_trait]
-impl LLMProviderTrait for AnthropicProvider {
-    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
-        // Filter out system messages (Anthropic doesn't support them in messages array)
-        let messages: Vec<Message> = request
+/// A streaming event; which fields are present depends on the event type.
+#[derive(Deserialize)]
+struct StreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    message: Option<StreamMessage>,
+    delta: Option<StreamDelta>,
+    usage: Option<StreamUsage>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    model: Option<String>,
+    usage: Option<StreamUsage>,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    text: Option<String>,
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    cache_read_input_tokens: Option<u32>,
+}
+
+impl From<StreamUsage> for Usage {
+    fn from(usage: StreamUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_input_tokens: usage.cache_read_input_tokens,
+            reasoning_tokens: None,
+        }
+    }
+}
+
+fn parse_stream_event(event: SseEvent) -> Result<Option<StreamChunk>> {
+    let event: StreamEvent = serde_json::from_str(&event.data)?;
+
+    match event.event_type.as_str() {
+        "message_start" => {
+            let (model, usage) = event
+                .message
+                .map(|message| (message.model, message.usage))
+                .unwrap_or_default();
+            Ok(Some(StreamChunk {
+                model,
+                usage: usage.map(Usage::from),
+                ..StreamChunk::delta("")
+            }))
+        }
+        "content_block_delta" => Ok(event
+            .delta
+            .and_then(|delta| delta.text)
+            .map(StreamChunk::delta)),
+        "message_delta" => Ok(Some(StreamChunk {
+            usage: event.usage.map(Usage::from),
+            finish_reason: event
+                .delta
+                .and_then(|delta| delta.stop_reason)
+                .as_deref()
+                .map(|reason| map_finish_reason(Provider::Anthropic, reason)),
+            ..StreamChunk::delta("")
+        })),
+        "error" => Err(TurbineError::ApiError(
+            event.error.map(|e| e.to_string()).unwrap_or_default(),
+        )),
+        _ => Ok(None),
+    }
+}
+
+impl AnthropicProvider {
+    /// Sends a streaming request and returns its server-sent events.
+    async fn open_stream(&self, request: &LLMRequest) -> Result<EventStream> {
+        let http_request = self.build_request(request, true)?;
+        let body = send_stream(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+        Ok(sse::event_stream(body))
+    }
+
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> Result<HttpRequest> {
+        // System messages move to the system prompt (Anthropic doesn't support them in the
+        // messages array), which also carries the JSON instruction for JSON output
+        let normalized = normalize_request(Provider::Anthropic, request);
+        let messages: Vec<AnthropicMessage> = normalized
             .messages
-            .iter()
-            .filter(|m| m.role != "system")
-            .cloned()
+            .into_iter()
+            .map(AnthropicMessage::from)
             .collect();
 
         if messages.is_empty() {
@@ -78,55 +522,115 @@ impl LLMProviderTrait for AnthropicProvider {
             ));
         }
 
-        // Build system prompt
-        let mut system_prompt = request.system_prompt.clone();
-
-        // For JSON output, add instruction to system prompt and use prefilling
-        if request.output_format == OutputFormat::Json {
-            let json_instruction = "You must respond with valid JSON only. Start your response with an opening brace {.";
-            system_prompt = Some(match system_prompt {
-                Some(existing) => format!("{} {}", existing, json_instruction),
-                None => json_instruction.to_string(),
-            });
-        }
-
         let body = AnthropicRequestBody {
             model: request.model.clone(),
             messages,
-            max_tokens: request.max_tokens.unwrap_or(1024),
-            system: system_prompt,
+            max_tokens: request.effective_max_tokens(),
+            system: normalized.system,
             temperature: request.temperature,
             top_p: request.top_p,
+            tools: request.tools.iter().map(AnthropicTool::from).collect(),
+            stream,
+            metadata: request
+                .anthropic_user_id
+                .clone()
+                .map(|user_id| Metadata { user_id }),
         };
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(format!("{}/messages", self.base_url))
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(TurbineError::ApiError(error_text));
+        let mut http_request =
+            HttpRequest::post_json(format!("{}/messages", self.base_url), &body)?
+                .with_header("x-api-key", self.api_key.get()?)
+                .with_header("anthropic-version", &self.version);
+        if !self.betas.is_empty() {
+            http_request = http_request.with_header("anthropic-beta", self.betas.join(","));
         }
 
-        let anthropic_response: AnthropicResponse = response.json().await?;
+        Ok(http_request)
+    }
+}
+
+#[async_trait]
+impl LLMProviderTrait for AnthropicProvider {
+    fn provider_kind(&self) -> Option<Provider> {
+        Some(Provider::Anthropic)
+    }
+
+    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let http_request = self.build_request(request, false)?;
+
+        let anthropic_response: AnthropicResponse = send_json(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+
+        if anthropic_response.content.is_empty() {
+            return Err(TurbineError::InvalidResponse(
+                "No content in response".to_string(),
+            ));
+        }
 
-        let content = anthropic_response
-            .content
-            .first()
-            .ok_or_else(|| TurbineError::InvalidResponse("No content in response".to_string()))?
-            .text
-            .clone();
+        // Cited answers are split across several text blocks, each carrying its own citations
+        let mut content = String::new();
+        let mut citations = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in anthropic_response.content {
+            match block.block_type.as_str() {
+                "text" => {
+                    content.push_str(&block.text);
+                    citations.extend(block.citations.into_iter().flatten().map(Citation::from));
+                }
+                "tool_use" => tool_calls.push(ToolCall {
+                    id: block.id.unwrap_or_default(),
+                    name: block.name.unwrap_or_default(),
+                    arguments: block.input.unwrap_or_default(),
+                }),
+                _ => {}
+            }
+        }
 
-        Ok(LLMResponse::new(
+        let mut response = LLMResponse::new(
             content,
             anthropic_response.usage.input_tokens,
             anthropic_response.usage.output_tokens,
-        ))
+        );
+        response.usage.cached_input_tokens = anthropic_response.usage.cache_read_input_tokens;
+        response.finish_reason = anthropic_response
+            .stop_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::Anthropic, reason));
+        if !citations.is_empty() {
+            response.citations = Some(citations);
+        }
+        if !tool_calls.is_empty() {
+            response.tool_calls = Some(tool_calls);
+        }
+
+        response.model = anthropic_response
+            .model
+            .unwrap_or_else(|| request.model.clone());
+        response.warnings = request_warnings(Provider::Anthropic, request);
+        Ok(response)
+    }
+
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let events = self.open_stream(request).await?;
+        Ok(ResponseStream::new(sse::chunk_stream(
+            events,
+            parse_stream_event,
+        )))
+    }
+
+    async fn stream_raw_events(&self, request: &LLMRequest) -> Result<RawEventStream> {
+        let events = self.open_stream(request).await?;
+        Ok(sse::raw_event_stream(events, Provider::Anthropic))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        let http_request = HttpRequest::get(format!("{}/models", self.base_url))
+            .with_header("x-api-key", self.api_key.get()?)
+            .with_header("anthropic-version", &self.version);
+        health::probe(self.transport.as_ref(), http_request).await
     }
 }
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use futures_util::stream;
+use std::time::Duration;
+
+use crate::{
+    error::Result,
+    health::HealthStatus,
+    models::{LLMRequest, LLMResponse, Usage},
+    streaming::{ResponseStream, StreamChunk},
+    tokens,
+    types::FinishReason,
+};
+
+use super::LLMProviderTrait;
+
+/// How [`EchoProvider`] changes the echoed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EchoMode {
+    /// Return the text unchanged (the default)
+    #[default]
+    Plain,
+    /// Return the text in upper case
+    Uppercase,
+    /// Return the text with its characters in reverse order
+    Reversed,
+}
+
+/// An offline provider that answers with the last user message.
+///
+/// It needs no API key or network and always answers the same way, which makes it handy
+/// for demos and for wiring up an application before choosing a real provider. Usage is
+/// estimated with [`tokens::estimate_request_tokens`] and [`tokens::estimate_tokens`].
+/// Streams yield the answer word by word.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::providers::echo::{EchoMode, EchoProvider};
+/// use turbine_llm::{FinishReason, LLMRequest, TurbineClient};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = TurbineClient::from_provider(EchoProvider::new().with_mode(EchoMode::Uppercase));
+///
+/// let response = client.send_request(&LLMRequest::quick("echo", "Hello there")).await?;
+/// assert_eq!(response.content, "HELLO THERE");
+/// assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+/// assert!(response.usage.input_tokens > 0);
+///
+/// let mut stream = client.send_request_stream(&LLMRequest::quick("echo", "Hello there")).await?;
+/// assert_eq!(stream.next().await.unwrap()?.delta, "HELLO ");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EchoProvider {
+    mode: EchoMode,
+}
+
+impl EchoProvider {
+    /// Creates a provider that echoes text unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the echoed text is changed.
+    pub fn with_mode(mut self, mode: EchoMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn answer(&self, request: &LLMRequest) -> String {
+        let text = request
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == "user")
+            .map_or("", |message| message.content.as_str());
+
+        match self.mode {
+            EchoMode::Plain => text.to_string(),
+            EchoMode::Uppercase => text.to_uppercase(),
+            EchoMode::Reversed => text.chars().rev().collect(),
+        }
+    }
+
+    fn usage(request: &LLMRequest, answer: &str) -> Usage {
+        Usage {
+            input_tokens: tokens::estimate_request_tokens(request),
+            output_tokens: tokens::estimate_tokens(answer),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProviderTrait for EchoProvider {
+    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let answer = self.answer(request);
+        let mut response = LLMResponse::new(answer, 0, 0);
+        response.usage = Self::usage(request, &response.content);
+        response.finish_reason = Some(FinishReason::Stop);
+        response.model = request.model.clone();
+        Ok(response)
+    }
+
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let answer = self.answer(request);
+        let mut last = StreamChunk::delta("");
+        last.usage = Some(Self::usage(request, &answer));
+        last.finish_reason = Some(FinishReason::Stop);
+        last.model = Some(request.model.clone());
+
+        let chunks: Vec<_> = answer
+            .split_inclusive(' ')
+            .map(|word| Ok(StreamChunk::delta(word)))
+            .chain([Ok(last)])
+            .collect();
+        Ok(ResponseStream::from_stream(stream::iter(chunks)))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        Ok(HealthStatus::Healthy {
+            latency: Duration::ZERO,
+        })
+    }
+}
@@ -1,34 +1,294 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::{
+    content::ContentPart,
     error::{Result, TurbineError},
-    models::{LLMRequest, LLMResponse},
-    types::{OutputFormat, Provider},
+    health::{self, HealthStatus},
+    models::{Choice, Citation, LLMRequest, LLMResponse, Usage},
+    streaming::{RawEventStream, ResponseStream, StreamChunk},
+    tools::{Tool, ToolCall},
+    transport::{HttpRequest, HttpTransport, ReqwestTransport},
+    types::{FinishReason, OutputFormat, Provider, api_key_from_env_var},
 };
 
-use super::LLMProviderTrait;
+use super::{
+    ApiKey, KeyProvider, LLMProviderTrait, map_finish_reason, normalize_messages, request_warnings,
+    send_json, send_stream,
+    sse::{self, EventStream, SseEvent},
+};
+
+/// Host of the Generative Language API.
+const GENERATIVE_LANGUAGE_HOST: &str = "https://generativelanguage.googleapis.com";
 
 pub struct GeminiProvider {
-    api_key: String,
-    base_url: String,
+    api_key: ApiKey,
+    host: String,
+    api_version: String,
+    /// Path between the API version and `/models`, e.g. the Vertex AI project and location
+    resource_path: String,
+    auth: GeminiAuth,
+    transport: Arc<dyn HttpTransport>,
+    max_request_bytes: Option<usize>,
+}
+
+/// How requests are authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GeminiAuth {
+    /// Generative Language API key sent as `x-goog-api-key`
+    ApiKey,
+    /// Vertex AI OAuth access token sent as `Authorization: Bearer`
+    Bearer,
 }
 
 impl GeminiProvider {
+    /// Creates a provider with the API key from the environment.
+    ///
+    /// `GEMINI_API_KEY` is used when set; otherwise the key is read from `GOOGLE_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if neither variable is set.
+    ///
+    /// # Example
+    ///
+    /// ```standalone_crate
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // SAFETY: no other thread reads the environment yet
+    /// unsafe {
+    ///     std::env::remove_var("GEMINI_API_KEY");
+    ///     std::env::set_var("GOOGLE_API_KEY", "google-key");
+    /// }
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"candidates":[{"content":{"parts":[{"text":"Hi!"}]}}],
+    ///         "usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":2}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new()?.with_transport(mock.clone());
+    /// provider.send_request(&LLMRequest::quick("gemini-2.0-flash", "Hello")).await?;
+    /// assert_eq!(mock.last_request().unwrap().header("x-goog-api-key"), Some("google-key"));
+    ///
+    /// // GEMINI_API_KEY takes precedence
+    /// unsafe { std::env::set_var("GEMINI_API_KEY", "gemini-key") };
+    /// assert!(GeminiProvider::new().is_ok());
+    /// assert_eq!(turbine_llm::Provider::Gemini.api_key_from_env()?, "gemini-key");
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn new() -> Result<Self> {
-        let api_key = std::env::var(Provider::Gemini.env_var())?;
+        let api_key = Provider::Gemini.api_key_from_env()?;
         Ok(Self {
-            api_key,
-            base_url: Provider::Gemini.base_url().to_string(),
+            api_key: ApiKey::Static(api_key),
+            host: GENERATIVE_LANGUAGE_HOST.to_string(),
+            api_version: "v1beta".to_string(),
+            resource_path: String::new(),
+            auth: GeminiAuth::ApiKey,
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         })
     }
 
+    /// Creates a provider with the API key read from the environment variable `name`
+    /// instead of `GEMINI_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if `name` is not set.
+    pub fn from_env_var(name: &str) -> Result<Self> {
+        Ok(Self::new_with_key(api_key_from_env_var(name)?))
+    }
+
     pub fn new_with_key(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
-            base_url: Provider::Gemini.base_url().to_string(),
+            api_key: ApiKey::Static(api_key.into()),
+            host: GENERATIVE_LANGUAGE_HOST.to_string(),
+            api_version: "v1beta".to_string(),
+            resource_path: String::new(),
+            auth: GeminiAuth::ApiKey,
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         }
     }
+
+    /// Creates a provider that targets Gemini on Vertex AI.
+    ///
+    /// Requests go to the Vertex AI endpoint of `project` in `region` and are authenticated
+    /// with an OAuth `access_token` (e.g. from `gcloud auth print-access-token`) instead of
+    /// an API key. Use `"global"` as the region for the global endpoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::gemini::GeminiProvider;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message, TurbineClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"candidates":[{"content":{"parts":[{"text":"Hi!"}]}}],
+    ///         "usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":2}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new_vertex("my-project", "us-central1", "ya29.token")
+    ///     .with_transport(mock.clone());
+    /// let client = TurbineClient::from_provider(provider);
+    ///
+    /// let request = LLMRequest::new("gemini-2.0-flash").with_message(Message::user("Hello"));
+    /// client.send_request(&request).await?;
+    ///
+    /// let sent = mock.last_request().unwrap();
+    /// assert_eq!(
+    ///     sent.url,
+    ///     "https://us-central1-aiplatform.googleapis.com/v1/projects/my-project/locations/us-central1/publishers/google/models/gemini-2.0-flash:generateContent"
+    /// );
+    /// assert_eq!(sent.header("authorization"), Some("Bearer ya29.token"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_vertex(
+        project: impl AsRef<str>,
+        region: impl AsRef<str>,
+        access_token: impl Into<String>,
+    ) -> Self {
+        let project = project.as_ref();
+        let region = region.as_ref();
+        let host = if region == "global" {
+            "https://aiplatform.googleapis.com".to_string()
+        } else {
+            format!("https://{}-aiplatform.googleapis.com", region)
+        };
+
+        Self {
+            api_key: ApiKey::Static(access_token.into()),
+            host,
+            api_version: "v1".to_string(),
+            resource_path: format!(
+                "/projects/{}/locations/{}/publishers/google",
+                project, region
+            ),
+            auth: GeminiAuth::Bearer,
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
+        }
+    }
+
+    /// Fetches the API key from `key_provider` before every request instead of using a
+    /// fixed key.
+    pub fn with_key_provider(mut self, key_provider: KeyProvider) -> Self {
+        self.api_key = ApiKey::Dynamic(key_provider);
+        self
+    }
+
+    /// Sets the API version requests are sent to.
+    ///
+    /// Defaults to `"v1beta"`, or `"v1"` for [`new_vertex`](Self::new_vertex). Some models
+    /// and features are only available on one version.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(
+    ///     MockTransport::new()
+    ///         .with_response(HttpResponse::new(
+    ///             200,
+    ///             r#"{"candidates":[{"content":{"parts":[{"text":"Hi!"}]}}],
+    ///                 "usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":2}}"#,
+    ///         ))
+    ///         .with_response(HttpResponse::new(200, "")),
+    /// );
+    /// let provider = GeminiProvider::new_with_key("sk-test")
+    ///     .with_api_version("v1")
+    ///     .with_transport(mock.clone());
+    /// let request = LLMRequest::quick("gemini-1.5-pro", "Hello");
+    ///
+    /// provider.send_request(&request).await?;
+    /// assert_eq!(
+    ///     mock.requests()[0].url,
+    ///     "https://generativelanguage.googleapis.com/v1/models/gemini-1.5-pro:generateContent"
+    /// );
+    ///
+    /// provider.stream_request(&request).await?;
+    /// assert_eq!(
+    ///     mock.requests()[1].url,
+    ///     "https://generativelanguage.googleapis.com/v1/models/gemini-1.5-pro:streamGenerateContent?alt=sse"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Sends requests to `base_url` instead of `https://generativelanguage.googleapis.com`,
+    /// e.g. a local test server or a proxy.
+    ///
+    /// Unlike the other providers, `base_url` excludes the API version: requests go to
+    /// `{base_url}/{api_version}/models/...`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.host = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Rejects request bodies larger than `max` bytes with
+    /// [`TurbineError::RequestTooLarge`] instead of sending them, whatever the transport.
+    /// No limit by default.
+    pub fn with_max_request_bytes(mut self, max: usize) -> Self {
+        self.max_request_bytes = Some(max);
+        self
+    }
+
+    /// Replaces the HTTP transport used to reach the API.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"candidates":[{"content":{"parts":[{"text":"Hi!"}]}}],
+    ///         "usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":2}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("gemini-2.0-flash")
+    ///     .with_system_prompt("Be brief.")
+    ///     .with_message(Message::user("Hello"));
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(response.content, "Hi!");
+    ///
+    /// let sent = mock.last_request().unwrap();
+    /// assert!(sent.url.ends_with("/models/gemini-2.0-flash:generateContent"));
+    /// assert_eq!(sent.json_body()?["systemInstruction"]["parts"][0]["text"], "Be brief.");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
 }
 
 #[derive(Serialize)]
@@ -40,6 +300,31 @@ struct GeminiRequestBody {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "generationConfig")]
     generation_config: Option<GenerationConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTool>,
+}
+
+#[derive(Serialize)]
+struct GeminiTool {
+    #[serde(rename = "functionDeclarations")]
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+impl From<&Tool> for FunctionDeclaration {
+    fn from(tool: &Tool) -> Self {
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            parameters: tool.parameters.clone(),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -54,8 +339,62 @@ struct Content {
 }
 
 #[derive(Serialize)]
-struct Part {
-    text: String,
+#[serde(untagged)]
+enum Part {
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: InlineData,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall")]
+        function_call: FunctionCallData,
+    },
+    FunctionResponse {
+        #[serde(rename = "functionResponse")]
+        function_response: FunctionResponseData,
+    },
+}
+
+#[derive(Serialize)]
+struct FunctionCallData {
+    name: String,
+    args: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct FunctionResponseData {
+    name: String,
+    response: serde_json::Value,
+}
+
+/// Wraps a tool result in the JSON object Gemini expects.
+fn function_response(content: &str) -> serde_json::Value {
+    match serde_json::from_str(content) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        _ => serde_json::json!({ "content": content }),
+    }
+}
+
+#[derive(Serialize)]
+struct InlineData {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    data: String,
+}
+
+impl From<ContentPart> for Part {
+    fn from(part: ContentPart) -> Self {
+        let (mime_type, data) = match part {
+            ContentPart::Image(image) => (image.media_type, image.data),
+            ContentPart::Document(document) => (document.media_type, document.data),
+        };
+        Part::InlineData {
+            inline_data: InlineData { mime_type, data },
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -71,61 +410,241 @@ struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "responseMimeType")]
     response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "responseJsonSchema")]
+    response_json_schema: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "candidateCount")]
+    candidate_count: Option<u32>,
 }
 
 #[derive(Deserialize)]
 struct GeminiResponse {
+    #[serde(default)]
     candidates: Vec<Candidate>,
     #[serde(rename = "usageMetadata")]
     usage_metadata: UsageMetadata,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<PromptFeedback>,
+    #[serde(rename = "modelVersion")]
+    model_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PromptFeedback {
+    #[serde(rename = "blockReason")]
+    block_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct Candidate {
+    // Absent when the response was blocked
+    #[serde(default)]
     content: ResponseContent,
+    #[serde(rename = "citationMetadata")]
+    citation_metadata: Option<CitationMetadata>,
+    #[serde(rename = "finishReason")]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
+struct CitationMetadata {
+    #[serde(rename = "citationSources", alias = "citations", default)]
+    citation_sources: Vec<CitationSource>,
+}
+
+#[derive(Deserialize)]
+struct CitationSource {
+    #[serde(rename = "startIndex")]
+    start_index: Option<u32>,
+    #[serde(rename = "endIndex")]
+    end_index: Option<u32>,
+    uri: Option<String>,
+    title: Option<String>,
+}
+
+impl From<CitationSource> for Citation {
+    fn from(source: CitationSource) -> Self {
+        Self {
+            cited_text: None,
+            title: source.title,
+            uri: source.uri,
+            document_index: None,
+            start_index: source.start_index,
+            end_index: source.end_index,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
 struct ResponseContent {
+    #[serde(default)]
     parts: Vec<ResponsePart>,
 }
 
 #[derive(Deserialize)]
 struct ResponsePart {
-    text: String,
+    text: Option<String>,
+    #[serde(rename = "functionCall")]
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
 }
 
 #[derive(Deserialize)]
 struct UsageMetadata {
     #[serde(rename = "promptTokenCount")]
     prompt_token_count: u32,
-    #[serde(rename = "candidatesTokenCount")]
+    #[serde(rename = "candidatesTokenCount", default)]
     candidates_token_count: u32,
+    #[serde(rename = "cachedContentTokenCount")]
+    cached_content_token_count: Option<u32>,
+    #[serde(rename = "thoughtsTokenCount")]
+    thoughts_token_count: Option<u32>,
 }
 
-#[async_trait]
-impl LLMProviderTrait for GeminiProvider {
-    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
-        // Convert messages to Gemini format
+impl From<UsageMetadata> for Usage {
+    fn from(usage: UsageMetadata) -> Self {
+        Self {
+            input_tokens: usage.prompt_token_count,
+            output_tokens: usage.candidates_token_count,
+            cached_input_tokens: usage.cached_content_token_count,
+            reasoning_tokens: usage.thoughts_token_count,
+        }
+    }
+}
+
+/// A streamed response fragment. Unlike [`GeminiResponse`], every field may be absent.
+#[derive(Deserialize)]
+struct GeminiStreamChunk {
+    #[serde(default)]
+    candidates: Vec<StreamCandidate>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<StreamUsageMetadata>,
+    #[serde(rename = "modelVersion")]
+    model_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamCandidate {
+    content: Option<StreamContent>,
+    #[serde(rename = "finishReason")]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamContent {
+    #[serde(default)]
+    parts: Vec<StreamPart>,
+}
+
+#[derive(Deserialize)]
+struct StreamPart {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct StreamUsageMetadata {
+    #[serde(rename = "promptTokenCount", default)]
+    prompt_token_count: u32,
+    #[serde(rename = "candidatesTokenCount", default)]
+    candidates_token_count: u32,
+    #[serde(rename = "cachedContentTokenCount")]
+    cached_content_token_count: Option<u32>,
+    #[serde(rename = "thoughtsTokenCount")]
+    thoughts_token_count: Option<u32>,
+}
+
+fn parse_stream_event(event: SseEvent) -> Result<Option<StreamChunk>> {
+    let chunk: GeminiStreamChunk = serde_json::from_str(&event.data)?;
+
+    let mut stream_chunk = StreamChunk::delta("");
+    stream_chunk.model = chunk.model_version;
+    if let Some(candidate) = chunk.candidates.into_iter().next() {
+        stream_chunk.delta = candidate
+            .content
+            .map(|content| content.parts.into_iter().map(|part| part.text).collect())
+            .unwrap_or_default();
+        stream_chunk.finish_reason = candidate
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::Gemini, reason));
+    }
+    stream_chunk.usage = chunk.usage_metadata.map(|usage| Usage {
+        input_tokens: usage.prompt_token_count,
+        output_tokens: usage.candidates_token_count,
+        cached_input_tokens: usage.cached_content_token_count,
+        reasoning_tokens: usage.thoughts_token_count,
+    });
+
+    Ok(Some(stream_chunk))
+}
+
+impl GeminiProvider {
+    /// Sends a streaming request and returns its server-sent events.
+    async fn open_stream(&self, request: &LLMRequest) -> Result<EventStream> {
+        let http_request = self.build_request(request, true)?;
+        let body = send_stream(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+        Ok(sse::event_stream(body))
+    }
+
+    /// Builds the request for the `generateContent` or, when streaming, the
+    /// `streamGenerateContent` endpoint.
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> Result<HttpRequest> {
+        // Convert messages to Gemini format; system messages move to the system instruction
+        let normalized = normalize_messages(Provider::Gemini, request);
         let mut contents: Vec<Content> = Vec::new();
 
-        for message in &request.messages {
-            // Map roles: assistant -> model, user -> user, system -> ignore (handled separately)
-            if message.role == "system" {
+        for message in &normalized.messages {
+            let role = message.role.as_str();
+
+            if let Some(tool_call_id) = &message.tool_call_id {
+                // Gemini links results to calls by function name
+                let name = request
+                    .messages
+                    .iter()
+                    .flat_map(|m| &m.tool_calls)
+                    .find(|call| &call.id == tool_call_id)
+                    .map_or(tool_call_id, |call| &call.name);
+                contents.push(Content {
+                    role: role.to_string(),
+                    parts: vec![Part::FunctionResponse {
+                        function_response: FunctionResponseData {
+                            name: name.clone(),
+                            response: function_response(&message.content),
+                        },
+                    }],
+                });
                 continue;
             }
 
-            let role = if message.role == "assistant" {
-                "model"
-            } else {
-                "user"
-            };
+            let mut parts: Vec<Part> = message.parts.iter().cloned().map(Part::from).collect();
+            if !message.content.is_empty() || (parts.is_empty() && message.tool_calls.is_empty()) {
+                parts.push(Part::Text {
+                    text: message.content.clone(),
+                });
+            }
+            parts.extend(message.tool_calls.iter().map(|call| Part::FunctionCall {
+                function_call: FunctionCallData {
+                    name: call.name.clone(),
+                    args: call.arguments.clone(),
+                },
+            }));
 
             contents.push(Content {
                 role: role.to_string(),
-                parts: vec![Part {
-                    text: message.content.clone(),
-                }],
+                parts,
             });
         }
 
@@ -136,68 +655,194 @@ impl LLMProviderTrait for GeminiProvider {
         }
 
         // System instruction
-        let system_instruction = request
-            .system_prompt
-            .as_ref()
-            .map(|prompt| SystemInstruction {
-                parts: vec![Part {
-                    text: prompt.clone(),
-                }],
-            });
+        let system_instruction = normalized.system.map(|prompt| SystemInstruction {
+            parts: vec![Part::Text { text: prompt }],
+        });
 
         // Generation config
-        let response_mime_type = if request.output_format == OutputFormat::Json {
-            Some("application/json".to_string())
-        } else {
-            None
+        let response_mime_type = request
+            .effective_output_format()
+            .is_json()
+            .then(|| "application/json".to_string());
+        let response_json_schema = match request.effective_output_format() {
+            OutputFormat::JsonSchema(schema) => Some(schema.clone()),
+            _ => None,
         };
 
         let generation_config = Some(GenerationConfig {
             temperature: request.temperature,
             top_p: request.top_p,
-            max_output_tokens: request.max_tokens,
+            max_output_tokens: Some(request.effective_max_tokens()),
             response_mime_type,
+            response_json_schema,
+            candidate_count: request.candidate_count,
         });
 
+        // All functions go into a single tool entry
+        let tools = if request.tools.is_empty() {
+            Vec::new()
+        } else {
+            vec![GeminiTool {
+                function_declarations: request
+                    .tools
+                    .iter()
+                    .map(FunctionDeclaration::from)
+                    .collect(),
+            }]
+        };
+
         let body = GeminiRequestBody {
             contents,
             system_instruction,
             generation_config,
+            tools,
         };
 
-        let client = reqwest::Client::new();
-        let url = format!("{}/models/{}:generateContent", self.base_url, request.model);
+        let method = if stream {
+            "streamGenerateContent?alt=sse"
+        } else {
+            "generateContent"
+        };
+        let url = format!(
+            "{}/{}{}/models/{}:{}",
+            self.host, self.api_version, self.resource_path, request.model, method
+        );
+        self.authorize(HttpRequest::post_json(url, &body)?)
+    }
+
+    /// Adds the credentials header to `http_request`.
+    fn authorize(&self, http_request: HttpRequest) -> Result<HttpRequest> {
+        Ok(match self.auth {
+            GeminiAuth::ApiKey => http_request.with_header("x-goog-api-key", self.api_key.get()?),
+            GeminiAuth::Bearer => {
+                http_request.with_header("Authorization", format!("Bearer {}", self.api_key.get()?))
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl LLMProviderTrait for GeminiProvider {
+    fn provider_kind(&self) -> Option<Provider> {
+        Some(Provider::Gemini)
+    }
+
+    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let http_request = self.build_request(request, false)?;
 
-        let response = client
-            .post(&url)
-            .header("x-goog-api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
+        let gemini_response: GeminiResponse = send_json(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(TurbineError::ApiError(error_text));
+        // With candidateCount > 1, every candidate is also returned as a choice
+        let choices = (gemini_response.candidates.len() > 1).then(|| {
+            gemini_response
+                .candidates
+                .iter()
+                .map(|candidate| Choice {
+                    content: candidate
+                        .content
+                        .parts
+                        .iter()
+                        .filter_map(|part| part.text.as_deref())
+                        .collect(),
+                    finish_reason: candidate
+                        .finish_reason
+                        .as_deref()
+                        .map(|reason| map_finish_reason(Provider::Gemini, reason)),
+                })
+                .collect()
+        });
+
+        // Blocked prompts and responses carry no output, but their tokens are reported
+        let usage = Usage::from(gemini_response.usage_metadata);
+        let Some(candidate) = gemini_response.candidates.into_iter().next() else {
+            let reason = match gemini_response
+                .prompt_feedback
+                .and_then(|feedback| feedback.block_reason)
+            {
+                Some(block_reason) => format!("prompt blocked ({})", block_reason),
+                None => "no candidates in response".to_string(),
+            };
+            return Err(TurbineError::EmptyResponse { reason, usage });
+        };
+
+        if candidate.content.parts.is_empty() {
+            let reason = match &candidate.finish_reason {
+                Some(finish_reason) => format!("no parts in response ({})", finish_reason),
+                None => "no parts in response".to_string(),
+            };
+            return Err(TurbineError::EmptyResponse { reason, usage });
+        }
+
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for part in candidate.content.parts {
+            if let Some(text) = part.text {
+                content.push_str(&text);
+            }
+            // Gemini function calls carry no id, so the function name links call and result
+            if let Some(call) = part.function_call {
+                tool_calls.push(ToolCall {
+                    id: call.name.clone(),
+                    name: call.name,
+                    arguments: call.args,
+                });
+            }
         }
 
-        let gemini_response: GeminiResponse = response.json().await?;
+        let mut response = LLMResponse::new(content, 0, 0);
+        response.usage = usage;
+        response.finish_reason = candidate
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::Gemini, reason));
+        // Gemini reports STOP for function calls
+        if !tool_calls.is_empty() {
+            response.finish_reason = Some(FinishReason::ToolCalls);
+            response.tool_calls = Some(tool_calls);
+        }
+        response.citations = candidate
+            .citation_metadata
+            .map(|metadata| {
+                metadata
+                    .citation_sources
+                    .into_iter()
+                    .map(Citation::from)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|citations| !citations.is_empty());
+        response.choices = choices;
 
-        let content = gemini_response
-            .candidates
-            .first()
-            .ok_or_else(|| TurbineError::InvalidResponse("No candidates in response".to_string()))?
-            .content
-            .parts
-            .first()
-            .ok_or_else(|| TurbineError::InvalidResponse("No parts in response".to_string()))?
-            .text
-            .clone();
-
-        Ok(LLMResponse::new(
-            content,
-            gemini_response.usage_metadata.prompt_token_count,
-            gemini_response.usage_metadata.candidates_token_count,
-        ))
+        response.model = gemini_response
+            .model_version
+            .unwrap_or_else(|| request.model.clone());
+        response.warnings = request_warnings(Provider::Gemini, request);
+        Ok(response)
+    }
+
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let events = self.open_stream(request).await?;
+        Ok(ResponseStream::new(sse::chunk_stream(
+            events,
+            parse_stream_event,
+        )))
+    }
+
+    async fn stream_raw_events(&self, request: &LLMRequest) -> Result<RawEventStream> {
+        let events = self.open_stream(request).await?;
+        Ok(sse::raw_event_stream(events, Provider::Gemini))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        let url = format!(
+            "{}/{}{}/models",
+            self.host, self.api_version, self.resource_path
+        );
+        let http_request = self.authorize(HttpRequest::get(url))?;
+        health::probe(self.transport.as_ref(), http_request).await
     }
 }
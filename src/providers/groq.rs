@@ -1,40 +1,123 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::{
+    content::ContentPart,
     error::{Result, TurbineError},
-    models::{LLMRequest, LLMResponse, Message},
-    types::{OutputFormat, Provider},
+    health::{self, HealthStatus},
+    models::{LLMRequest, LLMResponse, Message, Usage},
+    streaming::{RawEventStream, ResponseStream, StreamChunk},
+    tools::{self, Tool, ToolCall},
+    transport::{HttpRequest, HttpTransport, ReqwestTransport},
+    types::{Provider, api_key_from_env_var},
 };
 
-use super::LLMProviderTrait;
+use super::{
+    ApiKey, KeyProvider, LLMProviderTrait, map_finish_reason, normalize_request, request_warnings,
+    send_json, send_stream,
+    sse::{self, EventStream, SseEvent},
+};
 
 pub struct GroqProvider {
-    api_key: String,
+    api_key: ApiKey,
     base_url: String,
+    transport: Arc<dyn HttpTransport>,
+    max_request_bytes: Option<usize>,
 }
 
 impl GroqProvider {
     pub fn new() -> Result<Self> {
-        let api_key = std::env::var(Provider::Groq.env_var())?;
+        let api_key = Provider::Groq.api_key_from_env()?;
         Ok(Self {
-            api_key,
+            api_key: ApiKey::Static(api_key),
             base_url: Provider::Groq.base_url().to_string(),
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         })
     }
 
+    /// Creates a provider with the API key read from the environment variable `name`
+    /// instead of `GROQ_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if `name` is not set.
+    pub fn from_env_var(name: &str) -> Result<Self> {
+        Ok(Self::new_with_key(api_key_from_env_var(name)?))
+    }
+
     pub fn new_with_key(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: ApiKey::Static(api_key.into()),
             base_url: Provider::Groq.base_url().to_string(),
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         }
     }
+
+    /// Fetches the API key from `key_provider` before every request instead of using a
+    /// fixed key.
+    pub fn with_key_provider(mut self, key_provider: KeyProvider) -> Self {
+        self.api_key = ApiKey::Dynamic(key_provider);
+        self
+    }
+
+    /// Sends requests to `base_url` instead of `https://api.groq.com/openai/v1`, e.g. a
+    /// local test server or a proxy. Paths such as `/chat/completions` are appended to it.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Rejects request bodies larger than `max` bytes with
+    /// [`TurbineError::RequestTooLarge`] instead of sending them, whatever the transport.
+    /// No limit by default.
+    pub fn with_max_request_bytes(mut self, max: usize) -> Self {
+        self.max_request_bytes = Some(max);
+        self
+    }
+
+    /// Replaces the HTTP transport used to reach the API.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, groq::GroqProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, Message};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"Hi!"}}],
+    ///         "usage":{"prompt_tokens":5,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = GroqProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::new("llama-3.3-70b-versatile")
+    ///     .with_system_prompt("Be brief.")
+    ///     .with_message(Message::user("Hello"));
+    /// let response = provider.send_request(&request).await?;
+    /// assert_eq!(response.content, "Hi!");
+    ///
+    /// let sent = mock.last_request().unwrap();
+    /// assert_eq!(sent.json_body()?["messages"][0]["role"], "system");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
 }
 
 #[derive(Serialize)]
 struct GroqRequestBody {
     model: String,
-    messages: Vec<Message>,
+    messages: Vec<GroqMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,6 +126,147 @@ struct GroqRequestBody {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<FunctionTool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct GroqMessage {
+    role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<GroqContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<RequestToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RequestToolCall {
+    id: String,
+    #[serde(rename = "type")]
+    call_type: &'static str,
+    function: RequestFunctionCall,
+}
+
+#[derive(Serialize)]
+struct RequestFunctionCall {
+    name: String,
+    arguments: String,
+}
+
+impl From<ToolCall> for RequestToolCall {
+    fn from(call: ToolCall) -> Self {
+        Self {
+            id: call.id,
+            call_type: "function",
+            function: RequestFunctionCall {
+                name: call.name,
+                arguments: call.arguments.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum GroqContent {
+    Text(String),
+    Parts(Vec<GroqContentPart>),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GroqContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Serialize)]
+struct ImageUrl {
+    url: String,
+}
+
+impl TryFrom<Message> for GroqMessage {
+    type Error = TurbineError;
+
+    fn try_from(message: Message) -> Result<Self> {
+        let tool_calls: Vec<RequestToolCall> = message
+            .tool_calls
+            .into_iter()
+            .map(RequestToolCall::from)
+            .collect();
+
+        if message.parts.is_empty() {
+            // Assistant turns that only call tools have no content
+            let content = (!message.content.is_empty() || tool_calls.is_empty())
+                .then_some(GroqContent::Text(message.content));
+            return Ok(Self {
+                role: message.role,
+                content,
+                tool_calls,
+                tool_call_id: message.tool_call_id,
+            });
+        }
+
+        let mut parts = Vec::new();
+        if !message.content.is_empty() {
+            parts.push(GroqContentPart::Text {
+                text: message.content,
+            });
+        }
+        for part in message.parts {
+            match part {
+                ContentPart::Image(image) => parts.push(GroqContentPart::ImageUrl {
+                    image_url: ImageUrl {
+                        url: image.data_url(),
+                    },
+                }),
+                ContentPart::Document(document) => {
+                    return Err(TurbineError::UnsupportedContent(format!(
+                        "Groq does not support document inputs ({})",
+                        document.media_type
+                    )));
+                }
+            }
+        }
+
+        Ok(Self {
+            role: message.role,
+            content: Some(GroqContent::Parts(parts)),
+            tool_calls,
+            tool_call_id: message.tool_call_id,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct FunctionTool {
+    #[serde(rename = "type")]
+    tool_type: &'static str,
+    function: FunctionDefinition,
+}
+
+#[derive(Serialize)]
+struct FunctionDefinition {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+impl From<&Tool> for FunctionTool {
+    fn from(tool: &Tool) -> Self {
+        Self {
+            tool_type: "function",
+            function: FunctionDefinition {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.parameters.clone(),
+            },
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -53,18 +277,46 @@ struct ResponseFormat {
 
 #[derive(Deserialize)]
 struct GroqResponse {
+    model: Option<String>,
     choices: Vec<Choice>,
     usage: UsageInfo,
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageContent,
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageContent {
-    content: String,
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ResponseToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ResponseToolCall {
+    id: String,
+    function: ResponseFunctionCall,
+}
+
+#[derive(Deserialize)]
+struct ResponseFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+impl From<ResponseToolCall> for ToolCall {
+    fn from(call: ResponseToolCall) -> Self {
+        Self {
+            id: call.id,
+            arguments: tools::parse_arguments(&call.function.arguments),
+            name: call.function.name,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -73,29 +325,89 @@ struct UsageInfo {
     completion_tokens: u32,
 }
 
-#[async_trait]
-impl LLMProviderTrait for GroqProvider {
-    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
-        let mut messages = request.messages.clone();
+#[derive(Deserialize)]
+struct GroqStreamChunk {
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<UsageInfo>,
+    x_groq: Option<GroqStreamMetadata>,
+    error: Option<serde_json::Value>,
+}
 
-        // Add system prompt as first message if provided
-        if let Some(system_prompt) = &request.system_prompt {
-            messages.insert(0, Message::system(system_prompt));
-        }
+#[derive(Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+    finish_reason: Option<String>,
+}
 
-        // If JSON output is requested, add JSON instruction to system prompt
-        if request.output_format == OutputFormat::Json {
-            let json_instruction = "You must respond with valid JSON only.";
-            if let Some(first_msg) = messages.first_mut() {
-                if first_msg.role == "system" {
-                    first_msg.content = format!("{} {}", first_msg.content, json_instruction);
-                }
-            } else {
-                messages.insert(0, Message::system(json_instruction));
-            }
+#[derive(Deserialize, Default)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GroqStreamMetadata {
+    usage: Option<UsageInfo>,
+}
+
+impl From<UsageInfo> for Usage {
+    fn from(usage: UsageInfo) -> Self {
+        Self {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            ..Default::default()
         }
+    }
+}
 
-        let response_format = if request.output_format == OutputFormat::Json {
+fn parse_stream_event(event: SseEvent) -> Result<Option<StreamChunk>> {
+    if event.data == "[DONE]" {
+        return Ok(None);
+    }
+
+    let chunk: GroqStreamChunk = serde_json::from_str(&event.data)?;
+    if let Some(error) = chunk.error {
+        return Err(TurbineError::ApiError(error.to_string()));
+    }
+
+    let mut stream_chunk = StreamChunk::delta("");
+    stream_chunk.model = chunk.model;
+    if let Some(choice) = chunk.choices.into_iter().next() {
+        stream_chunk.delta = choice.delta.content.unwrap_or_default();
+        stream_chunk.finish_reason = choice
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::Groq, reason));
+    }
+    stream_chunk.usage = chunk
+        .usage
+        .or(chunk.x_groq.and_then(|meta| meta.usage))
+        .map(Usage::from);
+
+    Ok(Some(stream_chunk))
+}
+
+impl GroqProvider {
+    /// Sends a streaming request and returns its server-sent events.
+    async fn open_stream(&self, request: &LLMRequest) -> Result<EventStream> {
+        let http_request = self.build_request(request, true)?;
+        let body = send_stream(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+        Ok(sse::event_stream(body))
+    }
+
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> Result<HttpRequest> {
+        // The system prompt becomes the first message, with the JSON instruction for JSON
+        // output
+        let messages = normalize_request(Provider::Groq, request).messages;
+
+        let response_format = if request.effective_output_format().is_json() {
             Some(ResponseFormat {
                 format_type: "json_object".to_string(),
             })
@@ -103,43 +415,93 @@ impl LLMProviderTrait for GroqProvider {
             None
         };
 
+        let messages = messages
+            .into_iter()
+            .map(GroqMessage::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
         let body = GroqRequestBody {
             model: request.model.clone(),
             messages,
-            max_tokens: request.max_tokens,
+            max_tokens: Some(request.effective_max_tokens()),
             temperature: request.temperature,
             top_p: request.top_p,
             response_format,
+            tools: request.tools.iter().map(FunctionTool::from).collect(),
+            stream,
         };
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(TurbineError::ApiError(error_text));
-        }
+        Ok(
+            HttpRequest::post_json(format!("{}/chat/completions", self.base_url), &body)?
+                .with_header("Authorization", format!("Bearer {}", self.api_key.get()?)),
+        )
+    }
+}
+
+#[async_trait]
+impl LLMProviderTrait for GroqProvider {
+    fn provider_kind(&self) -> Option<Provider> {
+        Some(Provider::Groq)
+    }
+
+    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let http_request = self.build_request(request, false)?;
 
-        let groq_response: GroqResponse = response.json().await?;
+        let groq_response: GroqResponse = send_json(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
 
-        let content = groq_response
-            .choices
-            .first()
-            .ok_or_else(|| TurbineError::InvalidResponse("No choices in response".to_string()))?
-            .message
-            .content
-            .clone();
+        let choice =
+            groq_response.choices.into_iter().next().ok_or_else(|| {
+                TurbineError::InvalidResponse("No choices in response".to_string())
+            })?;
 
-        Ok(LLMResponse::new(
-            content,
+        // Tool-only turns have no text content
+        let mut response = LLMResponse::new(
+            choice.message.content.unwrap_or_default(),
             groq_response.usage.prompt_tokens,
             groq_response.usage.completion_tokens,
-        ))
+        );
+        response.finish_reason = choice
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::Groq, reason));
+        if !choice.message.tool_calls.is_empty() {
+            response.tool_calls = Some(
+                choice
+                    .message
+                    .tool_calls
+                    .into_iter()
+                    .map(ToolCall::from)
+                    .collect(),
+            );
+        }
+
+        response.system_fingerprint = groq_response.system_fingerprint;
+        response.model = groq_response.model.unwrap_or_else(|| request.model.clone());
+        response.warnings = request_warnings(Provider::Groq, request);
+        Ok(response)
+    }
+
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let events = self.open_stream(request).await?;
+        Ok(ResponseStream::new(sse::chunk_stream(
+            events,
+            parse_stream_event,
+        )))
+    }
+
+    async fn stream_raw_events(&self, request: &LLMRequest) -> Result<RawEventStream> {
+        let events = self.open_stream(request).await?;
+        Ok(sse::raw_event_stream(events, Provider::Groq))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        let http_request = HttpRequest::get(format!("{}/models", self.base_url))
+            .with_header("Authorization", format!("Bearer {}", self.api_key.get()?));
+        health::probe(self.transport.as_ref(), http_request).await
     }
 }
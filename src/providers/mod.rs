@@ -1,15 +1,264 @@
 pub mod anthropic;
+pub mod echo;
 pub mod gemini;
 pub mod groq;
+mod normalize;
 pub mod openai;
+pub(crate) mod sse;
+
+pub use normalize::{NormalizedMessages, NormalizedRequest, normalize_messages, normalize_request};
+pub(crate) use normalize::{openai_token_limits, request_warnings};
 
 use crate::{
-    error::Result,
+    embeddings::{EmbeddingRequest, EmbeddingResponse},
+    error::{Result, TurbineError},
+    health::HealthStatus,
     models::{LLMRequest, LLMResponse},
+    streaming::{RawEventStream, ResponseStream, StreamChunk},
+    transport::{ByteStream, HttpRequest, HttpTransport},
+    types::{FinishReason, Provider},
 };
 use async_trait::async_trait;
+use futures_util::stream;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// A callback returning the API key to use for the next request.
+///
+/// Providers configured with `with_key_provider()` call it before every request instead of
+/// caching a key, which supports rotating or short-lived credentials (e.g. from Vault).
+pub type KeyProvider = Arc<dyn Fn() -> Result<String> + Send + Sync>;
+
+/// A provider's API key: fixed at construction or fetched per request.
+#[derive(Clone)]
+pub(crate) enum ApiKey {
+    Static(String),
+    Dynamic(KeyProvider),
+}
+
+impl ApiKey {
+    /// Returns the key for the next request.
+    pub(crate) fn get(&self) -> Result<String> {
+        match self {
+            ApiKey::Static(key) => Ok(key.clone()),
+            ApiKey::Dynamic(provider) => provider(),
+        }
+    }
+}
 
 #[async_trait]
 pub trait LLMProviderTrait: Send + Sync {
     async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse>;
+
+    /// Returns which built-in provider this is, or `None` for custom providers.
+    fn provider_kind(&self) -> Option<Provider> {
+        None
+    }
+
+    /// Sends a request and streams the response.
+    ///
+    /// The default implementation sends a regular request and yields the whole response
+    /// as a single chunk, so providers without native streaming still work.
+    ///
+    /// The built-in providers stream over SSE, but the returned [`ResponseStream`] wraps any
+    /// stream of [`StreamChunk`]s, so a custom provider can stream over a WebSocket or any
+    /// other channel. Yield text deltas, then usage and finish reason as they become known;
+    /// the [`ResponseStream`] aggregates them into the terminal chunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use futures_util::stream;
+    /// use turbine_llm::providers::LLMProviderTrait;
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::{LLMRequest, LLMResponse, Message, Result, TurbineClient};
+    ///
+    /// /// Streams from a gateway that pushes frames over a socket.
+    /// struct SocketProvider;
+    ///
+    /// #[async_trait]
+    /// impl LLMProviderTrait for SocketProvider {
+    ///     async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+    ///         self.stream_request(request).await?.into_response().await
+    ///     }
+    ///
+    ///     async fn stream_request(&self, _request: &LLMRequest) -> Result<ResponseStream> {
+    ///         // Stand-in for a WebSocket: frames arrive on a channel
+    ///         let (tx, rx) = tokio::sync::mpsc::channel(8);
+    ///         tokio::spawn(async move {
+    ///             for frame in ["Hello", " over", " a socket"] {
+    ///                 let _ = tx.send(Ok(StreamChunk::delta(frame))).await;
+    ///             }
+    ///         });
+    ///         Ok(ResponseStream::from_stream(stream::unfold(rx, |mut rx| async move {
+    ///             rx.recv().await.map(|chunk| (chunk, rx))
+    ///         })))
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let client = TurbineClient::from_provider(SocketProvider);
+    /// let request = LLMRequest::new("gateway-model").with_message(Message::user("Hi"));
+    /// let response = client.send_request_stream(&request).await?.into_response().await?;
+    /// assert_eq!(response.content, "Hello over a socket");
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let response = self.send_request(request).await?;
+        let chunk = StreamChunk {
+            delta: response.content,
+            done: false,
+            usage: Some(response.usage),
+            finish_reason: response.finish_reason,
+            model: Some(response.model),
+        };
+        Ok(ResponseStream::new(Box::pin(stream::iter([Ok(chunk)]))))
+    }
+
+    /// Sends a request with streaming and returns the server-sent events unparsed.
+    ///
+    /// The built-in providers build [`stream_request`](Self::stream_request) on the same
+    /// events. The default implementation returns [`TurbineError::InvalidRequest`].
+    async fn stream_raw_events(&self, _request: &LLMRequest) -> Result<RawEventStream> {
+        Err(TurbineError::InvalidRequest(
+            "Raw stream events are not supported by this provider".to_string(),
+        ))
+    }
+
+    /// Checks whether the provider's API is reachable and accepts the API key.
+    ///
+    /// The built-in providers list the available models, which costs no tokens. The
+    /// default implementation returns [`TurbineError::InvalidRequest`].
+    async fn health_check(&self) -> Result<HealthStatus> {
+        Err(TurbineError::InvalidRequest(
+            "Health checks are not supported by this provider".to_string(),
+        ))
+    }
+
+    /// Embeds texts.
+    ///
+    /// The default implementation returns [`TurbineError::InvalidRequest`] for providers
+    /// without an embeddings API.
+    async fn embed(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        Err(TurbineError::InvalidRequest(format!(
+            "Embeddings are not supported for model {}",
+            request.model
+        )))
+    }
+}
+
+/// Sends `request` through `transport` and deserializes a successful JSON response body.
+pub(crate) async fn send_json<T: DeserializeOwned>(
+    transport: &dyn HttpTransport,
+    request: HttpRequest,
+    max_request_bytes: Option<usize>,
+) -> Result<T> {
+    check_body_size(&request, max_request_bytes)?;
+    let response = transport.send(request).await?;
+    let text = response.text()?;
+
+    if !response.is_success() {
+        return Err(api_error(response.status, text));
+    }
+
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Rejects a serialized body larger than `limit` before it reaches the transport.
+fn check_body_size(request: &HttpRequest, limit: Option<usize>) -> Result<()> {
+    match (limit, &request.body) {
+        (Some(limit), Some(body)) if body.len() > limit => Err(TurbineError::RequestTooLarge {
+            size: body.len(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Maps the finish or stop reason `provider` reports to a [`FinishReason`].
+pub(crate) fn map_finish_reason(provider: Provider, reason: &str) -> FinishReason {
+    match (provider, reason) {
+        (Provider::OpenAI | Provider::Groq, "stop")
+        | (Provider::Anthropic, "end_turn" | "stop_sequence")
+        | (Provider::Gemini, "STOP") => FinishReason::Stop,
+        (Provider::OpenAI | Provider::Groq, "length")
+        | (Provider::Anthropic, "max_tokens")
+        | (Provider::Gemini, "MAX_TOKENS") => FinishReason::Length,
+        (Provider::OpenAI | Provider::Groq, "tool_calls" | "function_call")
+        | (Provider::Anthropic, "tool_use") => FinishReason::ToolCalls,
+        (Provider::OpenAI | Provider::Groq, "content_filter")
+        | (Provider::Anthropic, "refusal")
+        | (
+            Provider::Gemini,
+            "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII",
+        ) => FinishReason::ContentFilter,
+        (_, other) => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// Status code Anthropic uses when its API is overloaded.
+const STATUS_OVERLOADED: u16 = 529;
+
+/// Status codes providers use when the API key is missing, invalid or lacks permission.
+const STATUS_UNAUTHORIZED: u16 = 401;
+const STATUS_FORBIDDEN: u16 = 403;
+
+/// Status code providers use when a rate limit is hit.
+const STATUS_TOO_MANY_REQUESTS: u16 = 429;
+
+/// Longest excerpt of a non-JSON error body kept in the error message, in characters.
+const ERROR_SNIPPET_CHARS: usize = 200;
+
+/// Converts an error response into a [`TurbineError`].
+///
+/// Provider errors are JSON and kept as is. Other bodies, such as the HTML page of a proxy,
+/// are reduced to the status code and a short excerpt.
+fn api_error(status: u16, body: &str) -> TurbineError {
+    let message = if serde_json::from_str::<serde_json::Value>(body).is_ok() {
+        body.to_string()
+    } else {
+        non_json_error(status, body)
+    };
+
+    match status {
+        STATUS_UNAUTHORIZED | STATUS_FORBIDDEN => TurbineError::Unauthorized(message),
+        STATUS_TOO_MANY_REQUESTS => TurbineError::RateLimited(message),
+        STATUS_OVERLOADED => TurbineError::Overloaded(message),
+        _ => TurbineError::ApiError(message),
+    }
+}
+
+/// Describes an error body that is not JSON by its status and a whitespace-collapsed,
+/// truncated excerpt.
+fn non_json_error(status: u16, body: &str) -> String {
+    let collapsed = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return format!("HTTP {} with an empty body", status);
+    }
+
+    let mut snippet: String = collapsed.chars().take(ERROR_SNIPPET_CHARS).collect();
+    if snippet.len() < collapsed.len() {
+        snippet.push_str("...");
+    }
+    format!("HTTP {} with a non-JSON body: {}", status, snippet)
+}
+
+/// Sends `request` through `transport` and returns the body stream of a successful response.
+pub(crate) async fn send_stream(
+    transport: &dyn HttpTransport,
+    request: HttpRequest,
+    max_request_bytes: Option<usize>,
+) -> Result<ByteStream> {
+    check_body_size(&request, max_request_bytes)?;
+    let response = transport.send_streaming(request).await?;
+
+    if !response.is_success() {
+        let response = response.into_response().await?;
+        return Err(api_error(response.status, response.text()?));
+    }
+
+    Ok(response.body)
 }
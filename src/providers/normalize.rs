@@ -0,0 +1,335 @@
+//! Role mapping and defaults shared by the providers.
+
+use serde::Serialize;
+
+use crate::{
+    models::{LLMRequest, Message},
+    tools::Tool,
+    types::{OutputFormat, Provider, is_openai_reasoning_model},
+};
+
+/// Instruction OpenAI and Groq append to the system prompt for JSON output.
+const DEFAULT_JSON_INSTRUCTION: &str = "You must respond with valid JSON only.";
+
+/// Instruction Anthropic appends to the system prompt for JSON output.
+const ANTHROPIC_JSON_INSTRUCTION: &str =
+    "You must respond with valid JSON only. Start your response with an opening brace {.";
+
+/// A request's system prompt and messages, mapped to the roles a provider accepts.
+#[derive(Debug, Clone)]
+pub struct NormalizedMessages {
+    /// System prompt for providers that take it outside the message list (Anthropic and
+    /// Gemini); always `None` for OpenAI and Groq
+    pub system: Option<String>,
+    /// The conversation with provider role names
+    pub messages: Vec<Message>,
+}
+
+/// Maps the system prompt and messages of `request` to what `provider` accepts.
+///
+/// - **OpenAI, Groq**: the system prompt is inserted as the first message; system messages
+///   stay where they are and roles are unchanged.
+/// - **Anthropic, Gemini**: the system prompt and the content of all system messages are
+///   joined into [`NormalizedMessages::system`]. Every role other than `assistant` becomes
+///   `user`, and Gemini renames `assistant` to `model`. Consecutive messages with the same
+///   role are merged, since both APIs expect alternating turns; tool results are never
+///   merged.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::providers::normalize_messages;
+/// use turbine_llm::{LLMRequest, Message, Provider};
+///
+/// let request = LLMRequest::new("model")
+///     .with_system_prompt("Be brief.")
+///     .with_message(Message::system("Answer in French."))
+///     .with_message(Message::user("Hello"))
+///     .with_message(Message::user("Are you there?"))
+///     .with_message(Message::assistant("Oui."));
+///
+/// // OpenAI keeps system messages in the conversation
+/// let openai = normalize_messages(Provider::OpenAI, &request);
+/// assert_eq!(openai.system, None);
+/// let roles: Vec<_> = openai.messages.iter().map(|m| m.role.as_str()).collect();
+/// assert_eq!(roles, ["system", "system", "user", "user", "assistant"]);
+/// assert_eq!(openai.messages[0].content, "Be brief.");
+///
+/// // Anthropic takes a separate system prompt and alternating turns
+/// let anthropic = normalize_messages(Provider::Anthropic, &request);
+/// assert_eq!(anthropic.system.as_deref(), Some("Be brief.\n\nAnswer in French."));
+/// let roles: Vec<_> = anthropic.messages.iter().map(|m| m.role.as_str()).collect();
+/// assert_eq!(roles, ["user", "assistant"]);
+/// assert_eq!(anthropic.messages[0].content, "Hello\n\nAre you there?");
+///
+/// // Gemini calls the assistant "model"
+/// let gemini = normalize_messages(Provider::Gemini, &request);
+/// let roles: Vec<_> = gemini.messages.iter().map(|m| m.role.as_str()).collect();
+/// assert_eq!(roles, ["user", "model"]);
+///
+/// // Tool results are sent as user turns but keep their own message
+/// let request = LLMRequest::new("model")
+///     .with_message(Message::tool_result("call_1", "18°C"))
+///     .with_message(Message::tool_result("call_2", "21°C"));
+/// let anthropic = normalize_messages(Provider::Anthropic, &request);
+/// assert_eq!(anthropic.messages.len(), 2);
+/// assert_eq!(anthropic.messages[1].role, "user");
+/// assert_eq!(anthropic.messages[1].tool_call_id.as_deref(), Some("call_2"));
+/// ```
+pub fn normalize_messages(provider: Provider, request: &LLMRequest) -> NormalizedMessages {
+    let assistant_role = match provider {
+        Provider::OpenAI | Provider::Groq => {
+            let mut messages = request.messages.clone();
+            if let Some(system_prompt) = &request.system_prompt {
+                messages.insert(0, Message::system(system_prompt));
+            }
+            return NormalizedMessages {
+                system: None,
+                messages,
+            };
+        }
+        Provider::Anthropic => "assistant",
+        Provider::Gemini => "model",
+    };
+
+    let mut system: Vec<&str> = request.system_prompt.iter().map(String::as_str).collect();
+    let mut messages: Vec<Message> = Vec::new();
+    for message in &request.messages {
+        if message.role == "system" {
+            system.push(&message.content);
+            continue;
+        }
+
+        let mut message = message.clone();
+        message.role = if message.role == "assistant" {
+            assistant_role.to_string()
+        } else {
+            "user".to_string()
+        };
+
+        match messages.last_mut() {
+            Some(previous) if can_merge(previous, &message) => merge(previous, message),
+            _ => messages.push(message),
+        }
+    }
+
+    NormalizedMessages {
+        system: (!system.is_empty()).then(|| system.join("\n\n")),
+        messages,
+    }
+}
+
+fn can_merge(previous: &Message, next: &Message) -> bool {
+    previous.role == next.role && previous.tool_call_id.is_none() && next.tool_call_id.is_none()
+}
+
+/// Appends the content, parts and tool calls of `next` to `previous`.
+fn merge(previous: &mut Message, next: Message) {
+    if previous.content.is_empty() {
+        previous.content = next.content;
+    } else if !next.content.is_empty() {
+        previous.content.push_str("\n\n");
+        previous.content.push_str(&next.content);
+    }
+    previous.parts.extend(next.parts);
+    previous.tool_calls.extend(next.tool_calls);
+}
+
+/// The effective request a provider sends: roles mapped, system prompt merged, JSON
+/// instruction added and defaults filled in.
+///
+/// It holds every setting the provider sends, with the fields it does not support left
+/// out, but not the provider's JSON encoding of messages and tools.
+///
+/// Returned by
+/// [`TurbineClient::send_request_normalized`](crate::TurbineClient::send_request_normalized)
+/// for reproducibility logs.
+#[derive(Debug, Clone, Serialize)]
+pub struct NormalizedRequest {
+    /// The provider the request is for, or `None` for custom providers
+    pub provider: Option<Provider>,
+    /// The model sent to the provider
+    pub model: String,
+    /// System prompt for providers that take it outside the message list (Anthropic and
+    /// Gemini)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// The conversation with provider role names
+    pub messages: Vec<Message>,
+    /// Maximum number of tokens to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// OpenAI's `max_completion_tokens`, sent instead of `max_tokens` when the request sets
+    /// it and for o-series models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+    /// Sampling temperature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Nucleus sampling threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Output format
+    pub output_format: OutputFormat,
+    /// Tools the model may call
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
+    /// Number of alternative responses (OpenAI `n`, Gemini `candidateCount`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_count: Option<u32>,
+    /// Processing tier (OpenAI)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+    /// End user identifier (Anthropic `metadata.user_id`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anthropic_user_id: Option<String>,
+}
+
+/// Returns the `max_tokens` and `max_completion_tokens` OpenAI is sent for `request`.
+///
+/// Reasoning models reject `max_tokens`, so their limit is sent as `max_completion_tokens`.
+pub(crate) fn openai_token_limits(request: &LLMRequest) -> (Option<u32>, Option<u32>) {
+    match request.max_completion_tokens {
+        Some(limit) => (None, Some(limit)),
+        None if is_openai_reasoning_model(&request.model) => {
+            (None, Some(request.effective_max_tokens()))
+        }
+        None => (Some(request.effective_max_tokens()), None),
+    }
+}
+
+/// Describes settings of `request` that `provider` ignores, for [`LLMResponse::warnings`].
+///
+/// [`LLMResponse::warnings`]: crate::LLMResponse::warnings
+pub(crate) fn request_warnings(provider: Provider, request: &LLMRequest) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if matches!(provider, Provider::OpenAI | Provider::Groq)
+        && request.effective_output_format().is_json()
+        && request.auto_json_instruction
+        && normalize_messages(provider, request)
+            .messages
+            .first()
+            .is_some_and(|first| first.role != "system")
+    {
+        warnings.push(
+            "JSON instruction not added because the conversation has no system prompt".to_string(),
+        );
+    }
+    if request.candidate_count.is_some_and(|count| count > 1)
+        && matches!(provider, Provider::Anthropic | Provider::Groq)
+    {
+        warnings.push(format!(
+            "candidate_count is not supported by {:?}; one response was generated",
+            provider
+        ));
+    }
+    if request.service_tier.is_some() && provider != Provider::OpenAI {
+        warnings.push(format!("service_tier is not supported by {:?}", provider));
+    }
+    if request.max_completion_tokens.is_some() && provider != Provider::OpenAI {
+        warnings.push(format!(
+            "max_completion_tokens is not supported by {:?}; max_tokens was used",
+            provider
+        ));
+    }
+    if request.anthropic_user_id.is_some() && provider != Provider::Anthropic {
+        warnings.push(format!("Anthropic metadata is not sent to {:?}", provider));
+    }
+
+    warnings
+}
+
+/// Applies what `provider` does to `request` before sending it.
+///
+/// On top of [`normalize_messages`], the JSON instruction is added for JSON output (see
+/// [`LLMRequest::with_auto_json_instruction`]), an unset `max_tokens` gets its default
+/// (sent as `max_completion_tokens` where OpenAI requires it), and settings the provider
+/// does not support are dropped.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::providers::normalize_request;
+/// use turbine_llm::{LLMRequest, Message, OutputFormat, Provider};
+///
+/// let request = LLMRequest::new("claude-3-5-haiku-20241022")
+///     .with_system_prompt("List the cities.")
+///     .with_message(Message::user("Paris and Rome"))
+///     .with_output_format(OutputFormat::Json);
+///
+/// let normalized = normalize_request(Provider::Anthropic, &request);
+/// assert_eq!(normalized.max_tokens, Some(1024));
+/// assert!(normalized.system.unwrap().starts_with("List the cities. You must respond with valid JSON"));
+///
+/// // o-series models take their limit as max_completion_tokens
+/// let request = LLMRequest::quick("o3-mini", "Name two rivers").with_candidate_count(2);
+/// let normalized = normalize_request(Provider::OpenAI, &request);
+/// assert_eq!((normalized.max_tokens, normalized.max_completion_tokens), (None, Some(1024)));
+/// assert_eq!(normalized.candidate_count, Some(2));
+///
+/// // Anthropic generates a single response
+/// assert_eq!(normalize_request(Provider::Anthropic, &request).candidate_count, None);
+/// ```
+pub fn normalize_request(provider: Provider, request: &LLMRequest) -> NormalizedRequest {
+    let NormalizedMessages {
+        mut system,
+        mut messages,
+    } = normalize_messages(provider, request);
+
+    if request.effective_output_format().is_json() && request.auto_json_instruction {
+        let default = match provider {
+            Provider::Anthropic => ANTHROPIC_JSON_INSTRUCTION,
+            _ => DEFAULT_JSON_INSTRUCTION,
+        };
+        let instruction = request.json_instruction.as_deref().unwrap_or(default);
+        match provider {
+            Provider::OpenAI | Provider::Groq => {
+                if let Some(first) = messages.first_mut() {
+                    if first.role == "system" {
+                        first.content = format!("{} {}", first.content, instruction);
+                    }
+                } else {
+                    messages.insert(0, Message::system(instruction));
+                }
+            }
+            Provider::Anthropic => {
+                system = Some(match system {
+                    Some(existing) => format!("{} {}", existing, instruction),
+                    None => instruction.to_string(),
+                });
+            }
+            // Gemini relies on its response MIME type alone
+            Provider::Gemini => {}
+        }
+    }
+
+    let (max_tokens, max_completion_tokens) = match provider {
+        Provider::OpenAI => openai_token_limits(request),
+        _ => (Some(request.effective_max_tokens()), None),
+    };
+
+    NormalizedRequest {
+        provider: Some(provider),
+        model: request.model.clone(),
+        system,
+        messages,
+        max_tokens,
+        max_completion_tokens,
+        temperature: request.temperature,
+        top_p: request.top_p,
+        output_format: request.effective_output_format().clone(),
+        tools: request.tools.clone(),
+        candidate_count: request
+            .candidate_count
+            .filter(|_| matches!(provider, Provider::OpenAI | Provider::Gemini)),
+        service_tier: request
+            .service_tier
+            .clone()
+            .filter(|_| provider == Provider::OpenAI),
+        anthropic_user_id: request
+            .anthropic_user_id
+            .clone()
+            .filter(|_| provider == Provider::Anthropic),
+    }
+}
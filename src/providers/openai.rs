@@ -1,48 +1,322 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::{
+    content::{ContentPart, ImageDetail},
+    embeddings::{EmbeddingRequest, EmbeddingResponse},
     error::{Result, TurbineError},
-    models::{LLMRequest, LLMResponse, Message},
-    types::{OutputFormat, Provider},
+    health::{self, HealthStatus},
+    models::{self, LLMRequest, LLMResponse, Message, Usage},
+    streaming::{RawEventStream, ResponseStream, StreamChunk},
+    tools::{self, Tool, ToolCall},
+    transport::{HttpRequest, HttpTransport, ReqwestTransport},
+    types::{Provider, Region, api_key_from_env_var},
 };
 
-use super::LLMProviderTrait;
+use super::{
+    ApiKey, KeyProvider, LLMProviderTrait, map_finish_reason, normalize_request,
+    openai_token_limits, request_warnings, send_json, send_stream,
+    sse::{self, EventStream, SseEvent},
+};
+
+/// Values accepted for `service_tier`.
+const SERVICE_TIERS: &[&str] = &["auto", "default", "flex", "priority"];
 
 pub struct OpenAIProvider {
-    api_key: String,
+    api_key: ApiKey,
     base_url: String,
+    transport: Arc<dyn HttpTransport>,
+    max_request_bytes: Option<usize>,
 }
 
 impl OpenAIProvider {
     pub fn new() -> Result<Self> {
-        let api_key = std::env::var(Provider::OpenAI.env_var())?;
+        let api_key = Provider::OpenAI.api_key_from_env()?;
         Ok(Self {
-            api_key,
+            api_key: ApiKey::Static(api_key),
             base_url: Provider::OpenAI.base_url().to_string(),
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
         })
     }
 
+    /// Creates a provider with the API key read from the environment variable `name`
+    /// instead of `OPENAI_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if `name` is not set.
+    pub fn from_env_var(name: &str) -> Result<Self> {
+        Ok(Self::new_with_key(api_key_from_env_var(name)?))
+    }
+
     pub fn new_with_key(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: ApiKey::Static(api_key.into()),
             base_url: Provider::OpenAI.base_url().to_string(),
+            transport: Arc::new(ReqwestTransport::new()),
+            max_request_bytes: None,
+        }
+    }
+
+    /// Fetches the API key from `key_provider` before every request instead of using a
+    /// fixed key.
+    pub fn with_key_provider(mut self, key_provider: KeyProvider) -> Self {
+        self.api_key = ApiKey::Dynamic(key_provider);
+        self
+    }
+
+    /// Sends requests to OpenAI's data residency endpoint for `region`, e.g.
+    /// `https://eu.api.openai.com/v1` for [`Region::Eu`].
+    ///
+    /// The project of the API key must be set up for that region.
+    pub fn with_region(mut self, region: Region) -> Self {
+        if let Some(base_url) = Provider::OpenAI.regional_base_url(region) {
+            self.base_url = base_url.to_string();
         }
+        self
+    }
+
+    /// Sends requests to `base_url` instead of `https://api.openai.com/v1`, e.g. a local
+    /// test server, a proxy or another OpenAI-compatible API.
+    ///
+    /// Paths such as `/chat/completions` are appended to it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::LLMRequest;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"choices":[{"message":{"content":"Hi!"}}],"usage":{"prompt_tokens":5,"completion_tokens":2}}"#,
+    /// )));
+    /// let provider = OpenAIProvider::new_with_key("sk-test")
+    ///     .with_base_url("https://localhost:8443/v1/")
+    ///     .with_transport(mock.clone());
+    ///
+    /// provider.send_request(&LLMRequest::quick("gpt-4o-mini", "Hello")).await?;
+    /// assert_eq!(mock.last_request().unwrap().url, "https://localhost:8443/v1/chat/completions");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Rejects request bodies larger than `max` bytes with
+    /// [`TurbineError::RequestTooLarge`] instead of sending them, whatever the transport.
+    /// No limit by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+    /// use turbine_llm::transport::MockTransport;
+    /// use turbine_llm::{LLMRequest, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mock = Arc::new(MockTransport::new());
+    /// let provider = OpenAIProvider::new_with_key("sk-test")
+    ///     .with_max_request_bytes(1024)
+    ///     .with_transport(mock.clone());
+    ///
+    /// let request = LLMRequest::quick("gpt-4o-mini", "x".repeat(2048));
+    /// let error = provider.send_request(&request).await.unwrap_err();
+    /// assert!(matches!(error, TurbineError::RequestTooLarge { limit: 1024, .. }));
+    /// assert!(mock.requests().is_empty());
+    /// # }
+    /// ```
+    pub fn with_max_request_bytes(mut self, max: usize) -> Self {
+        self.max_request_bytes = Some(max);
+        self
+    }
+
+    /// Replaces the HTTP transport used to reach the API.
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
     }
 }
 
 #[derive(Serialize)]
 struct OpenAIRequestBody {
     model: String,
-    messages: Vec<Message>,
+    messages: Vec<OpenAIMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<FunctionTool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_tier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Serialize)]
+struct OpenAIMessage {
+    role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<OpenAIContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<RequestToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RequestToolCall {
+    id: String,
+    #[serde(rename = "type")]
+    call_type: &'static str,
+    function: RequestFunctionCall,
+}
+
+#[derive(Serialize)]
+struct RequestFunctionCall {
+    name: String,
+    arguments: String,
+}
+
+impl From<ToolCall> for RequestToolCall {
+    fn from(call: ToolCall) -> Self {
+        Self {
+            id: call.id,
+            call_type: "function",
+            function: RequestFunctionCall {
+                name: call.name,
+                arguments: call.arguments.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Serialize)]
+struct ImageUrl {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<ImageDetail>,
+}
+
+impl TryFrom<Message> for OpenAIMessage {
+    type Error = TurbineError;
+
+    fn try_from(message: Message) -> Result<Self> {
+        let tool_calls: Vec<RequestToolCall> = message
+            .tool_calls
+            .into_iter()
+            .map(RequestToolCall::from)
+            .collect();
+
+        if message.parts.is_empty() {
+            // Assistant turns that only call tools have no content
+            let content = (!message.content.is_empty() || tool_calls.is_empty())
+                .then_some(OpenAIContent::Text(message.content));
+            return Ok(Self {
+                role: message.role,
+                content,
+                tool_calls,
+                tool_call_id: message.tool_call_id,
+            });
+        }
+
+        let mut parts = Vec::new();
+        if !message.content.is_empty() {
+            parts.push(OpenAIContentPart::Text {
+                text: message.content,
+            });
+        }
+        for part in message.parts {
+            match part {
+                ContentPart::Image(image) => parts.push(OpenAIContentPart::ImageUrl {
+                    image_url: ImageUrl {
+                        url: image.data_url(),
+                        detail: image.detail,
+                    },
+                }),
+                ContentPart::Document(document) => {
+                    return Err(TurbineError::UnsupportedContent(format!(
+                        "OpenAI does not support document inputs ({})",
+                        document.media_type
+                    )));
+                }
+            }
+        }
+
+        Ok(Self {
+            role: message.role,
+            content: Some(OpenAIContent::Parts(parts)),
+            tool_calls,
+            tool_call_id: message.tool_call_id,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct FunctionTool {
+    #[serde(rename = "type")]
+    tool_type: &'static str,
+    function: FunctionDefinition,
+}
+
+#[derive(Serialize)]
+struct FunctionDefinition {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+impl From<&Tool> for FunctionTool {
+    fn from(tool: &Tool) -> Self {
+        Self {
+            tool_type: "function",
+            function: FunctionDefinition {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.parameters.clone(),
+            },
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -53,49 +327,174 @@ struct ResponseFormat {
 
 #[derive(Deserialize)]
 struct OpenAIResponse {
+    model: Option<String>,
     choices: Vec<Choice>,
     usage: UsageInfo,
+    service_tier: Option<String>,
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageContent,
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageContent {
-    content: String,
+    content: Option<String>,
+    refusal: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ResponseToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ResponseToolCall {
+    id: String,
+    function: ResponseFunctionCall,
+}
+
+#[derive(Deserialize)]
+struct ResponseFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+impl From<ResponseToolCall> for ToolCall {
+    fn from(call: ResponseToolCall) -> Self {
+        Self {
+            id: call.id,
+            arguments: tools::parse_arguments(&call.function.arguments),
+            name: call.function.name,
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct UsageInfo {
     prompt_tokens: u32,
     completion_tokens: u32,
+    prompt_tokens_details: Option<PromptTokensDetails>,
+    completion_tokens_details: Option<CompletionTokensDetails>,
 }
 
-#[async_trait]
-impl LLMProviderTrait for OpenAIProvider {
-    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
-        let mut messages = request.messages.clone();
+#[derive(Deserialize)]
+struct PromptTokensDetails {
+    cached_tokens: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct CompletionTokensDetails {
+    reasoning_tokens: Option<u32>,
+}
 
-        // Add system prompt as first message if provided
-        if let Some(system_prompt) = &request.system_prompt {
-            messages.insert(0, Message::system(system_prompt));
+#[derive(Deserialize)]
+struct OpenAIStreamChunk {
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<UsageInfo>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+    usage: EmbeddingUsage,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingUsage {
+    prompt_tokens: u32,
+}
+
+impl From<UsageInfo> for Usage {
+    fn from(usage: UsageInfo) -> Self {
+        Self {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            cached_input_tokens: usage
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            reasoning_tokens: usage
+                .completion_tokens_details
+                .and_then(|details| details.reasoning_tokens),
         }
+    }
+}
 
-        // If JSON output is requested, add JSON instruction to system prompt
-        if request.output_format == OutputFormat::Json {
-            let json_instruction = "You must respond with valid JSON only.";
-            if let Some(first_msg) = messages.first_mut() {
-                if first_msg.role == "system" {
-                    first_msg.content = format!("{} {}", first_msg.content, json_instruction);
-                }
-            } else {
-                messages.insert(0, Message::system(json_instruction));
-            }
+fn parse_stream_event(event: SseEvent) -> Result<Option<StreamChunk>> {
+    if event.data == "[DONE]" {
+        return Ok(None);
+    }
+
+    let chunk: OpenAIStreamChunk = serde_json::from_str(&event.data)?;
+    if let Some(error) = chunk.error {
+        return Err(TurbineError::ApiError(error.to_string()));
+    }
+
+    let mut stream_chunk = StreamChunk::delta("");
+    stream_chunk.model = chunk.model;
+    if let Some(choice) = chunk.choices.into_iter().next() {
+        stream_chunk.delta = choice.delta.content.unwrap_or_default();
+        stream_chunk.finish_reason = choice
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::OpenAI, reason));
+    }
+    stream_chunk.usage = chunk.usage.map(Usage::from);
+
+    Ok(Some(stream_chunk))
+}
+
+impl OpenAIProvider {
+    /// Sends a streaming request and returns its server-sent events.
+    async fn open_stream(&self, request: &LLMRequest) -> Result<EventStream> {
+        let http_request = self.build_request(request, true)?;
+        let body = send_stream(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+        Ok(sse::event_stream(body))
+    }
+
+    fn build_request(&self, request: &LLMRequest, stream: bool) -> Result<HttpRequest> {
+        // The system prompt becomes the first message, with the JSON instruction for JSON
+        // output
+        let messages = normalize_request(Provider::OpenAI, request).messages;
+
+        if let Some(service_tier) = &request.service_tier
+            && !SERVICE_TIERS.contains(&service_tier.as_str())
+        {
+            return Err(TurbineError::InvalidRequest(format!(
+                "Unknown service tier {:?}, expected one of {}",
+                service_tier,
+                SERVICE_TIERS.join(", ")
+            )));
         }
 
-        let response_format = if request.output_format == OutputFormat::Json {
+        let response_format = if request.effective_output_format().is_json() {
             Some(ResponseFormat {
                 format_type: "json_object".to_string(),
             })
@@ -103,43 +502,147 @@ impl LLMProviderTrait for OpenAIProvider {
             None
         };
 
+        let messages = messages
+            .into_iter()
+            .map(OpenAIMessage::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let (max_tokens, max_completion_tokens) = openai_token_limits(request);
+
         let body = OpenAIRequestBody {
             model: request.model.clone(),
             messages,
-            max_tokens: request.max_tokens,
+            max_tokens,
+            max_completion_tokens,
             temperature: request.temperature,
             top_p: request.top_p,
             response_format,
+            tools: request.tools.iter().map(FunctionTool::from).collect(),
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+            service_tier: request.service_tier.clone(),
+            // Streamed choices would interleave, so streams always have one
+            n: request.candidate_count.filter(|_| !stream),
         };
 
-        let client = reqwest::Client::new();
-        let response = client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(TurbineError::ApiError(error_text));
+        Ok(
+            HttpRequest::post_json(format!("{}/chat/completions", self.base_url), &body)?
+                .with_header("Authorization", format!("Bearer {}", self.api_key.get()?)),
+        )
+    }
+}
+
+#[async_trait]
+impl LLMProviderTrait for OpenAIProvider {
+    fn provider_kind(&self) -> Option<Provider> {
+        Some(Provider::OpenAI)
+    }
+
+    async fn send_request(&self, request: &LLMRequest) -> Result<LLMResponse> {
+        let http_request = self.build_request(request, false)?;
+
+        let openai_response: OpenAIResponse = send_json(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+
+        // With n > 1, every choice is also returned with its finish reason
+        let choices = (openai_response.choices.len() > 1).then(|| {
+            openai_response
+                .choices
+                .iter()
+                .map(|choice| models::Choice {
+                    content: choice.message.content.clone().unwrap_or_default(),
+                    finish_reason: choice
+                        .finish_reason
+                        .as_deref()
+                        .map(|reason| map_finish_reason(Provider::OpenAI, reason)),
+                })
+                .collect()
+        });
+
+        let choice =
+            openai_response.choices.into_iter().next().ok_or_else(|| {
+                TurbineError::InvalidResponse("No choices in response".to_string())
+            })?;
+
+        // Tool-only turns have no text content
+        let mut response = LLMResponse::new(choice.message.content.unwrap_or_default(), 0, 0);
+        response.usage = openai_response.usage.into();
+        response.finish_reason = choice
+            .finish_reason
+            .as_deref()
+            .map(|reason| map_finish_reason(Provider::OpenAI, reason));
+        response.refusal = choice.message.refusal;
+        response.service_tier = openai_response.service_tier;
+        response.system_fingerprint = openai_response.system_fingerprint;
+        response.choices = choices;
+        if !choice.message.tool_calls.is_empty() {
+            response.tool_calls = Some(
+                choice
+                    .message
+                    .tool_calls
+                    .into_iter()
+                    .map(ToolCall::from)
+                    .collect(),
+            );
         }
 
-        let openai_response: OpenAIResponse = response.json().await?;
-
-        let content = openai_response
-            .choices
-            .first()
-            .ok_or_else(|| TurbineError::InvalidResponse("No choices in response".to_string()))?
-            .message
-            .content
-            .clone();
-
-        Ok(LLMResponse::new(
-            content,
-            openai_response.usage.prompt_tokens,
-            openai_response.usage.completion_tokens,
-        ))
+        response.model = openai_response
+            .model
+            .unwrap_or_else(|| request.model.clone());
+        response.warnings = request_warnings(Provider::OpenAI, request);
+        Ok(response)
+    }
+
+    async fn stream_request(&self, request: &LLMRequest) -> Result<ResponseStream> {
+        let events = self.open_stream(request).await?;
+        Ok(ResponseStream::new(sse::chunk_stream(
+            events,
+            parse_stream_event,
+        )))
+    }
+
+    async fn stream_raw_events(&self, request: &LLMRequest) -> Result<RawEventStream> {
+        let events = self.open_stream(request).await?;
+        Ok(sse::raw_event_stream(events, Provider::OpenAI))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        let http_request = HttpRequest::get(format!("{}/models", self.base_url))
+            .with_header("Authorization", format!("Bearer {}", self.api_key.get()?));
+        health::probe(self.transport.as_ref(), http_request).await
+    }
+
+    async fn embed(&self, request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
+        request.validate()?;
+
+        let http_request =
+            HttpRequest::post_json(format!("{}/embeddings", self.base_url), request)?
+                .with_header("Authorization", format!("Bearer {}", self.api_key.get()?));
+
+        let mut embeddings_response: EmbeddingsResponse = send_json(
+            self.transport.as_ref(),
+            http_request,
+            self.max_request_bytes,
+        )
+        .await?;
+        embeddings_response.data.sort_by_key(|data| data.index);
+
+        Ok(EmbeddingResponse {
+            embeddings: embeddings_response
+                .data
+                .into_iter()
+                .map(|data| data.embedding)
+                .collect(),
+            usage: Usage {
+                input_tokens: embeddings_response.usage.prompt_tokens,
+                ..Default::default()
+            },
+        })
     }
 }
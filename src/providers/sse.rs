@@ -0,0 +1,194 @@
+//! Server-sent events decoding shared by the streaming providers.
+
+use std::pin::Pin;
+
+use futures_util::{Stream, StreamExt, stream};
+
+use crate::{
+    error::Result,
+    streaming::{ChunkStream, RawEvent, RawEventStream, StreamChunk},
+    transport::ByteStream,
+    types::Provider,
+};
+
+/// A boxed stream of decoded server-sent events.
+pub(crate) type EventStream = Pin<Box<dyn Stream<Item = Result<SseEvent>> + Send>>;
+
+/// A single server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    /// The `event:` field, if present
+    pub event: Option<String>,
+    /// The `data:` lines joined with newlines
+    pub data: String,
+}
+
+/// Incrementally splits a byte stream into server-sent events.
+///
+/// Bytes are buffered until a blank line terminates an event, so multi-byte characters
+/// split across network chunks are decoded correctly. Each byte is searched for the
+/// terminator about once, so large events arriving in small chunks stay linear.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    /// Length of the buffer prefix already searched without finding an event end
+    scanned: usize,
+}
+
+impl SseDecoder {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete event, if one is buffered.
+    pub fn next_event(&mut self) -> Option<SseEvent> {
+        loop {
+            let Some((end, separator)) = find_event_end(&self.buffer, self.scanned) else {
+                // Back up so that a `\r\n\r\n` split across chunks is still found
+                self.scanned = self.buffer.len().saturating_sub(3);
+                return None;
+            };
+            self.scanned = 0;
+            let raw: Vec<u8> = self.buffer.drain(..end + separator).take(end).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&raw)) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Returns a trailing event that was not terminated by a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let raw = std::mem::take(&mut self.buffer);
+        self.scanned = 0;
+        parse_event(&String::from_utf8_lossy(&raw))
+    }
+}
+
+/// Returns the position and length of the first blank line at or after `from`.
+fn find_event_end(buffer: &[u8], from: usize) -> Option<(usize, usize)> {
+    (from..buffer.len()).find_map(|i| {
+        if buffer[i..].starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else if buffer[i..].starts_with(b"\n\n") {
+            Some((i, 2))
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_event(raw: &str) -> Option<SseEvent> {
+    let mut event = SseEvent::default();
+    let mut has_data = false;
+
+    for line in raw.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event.event = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if has_data {
+                event.data.push('\n');
+            }
+            event
+                .data
+                .push_str(value.strip_prefix(' ').unwrap_or(value));
+            has_data = true;
+        }
+    }
+
+    has_data.then_some(event)
+}
+
+struct SseState {
+    body: ByteStream,
+    decoder: SseDecoder,
+    body_done: bool,
+    failed: bool,
+}
+
+/// Decodes an SSE body into events.
+///
+/// The body is only read when the returned stream is polled. A body error ends the stream.
+pub(crate) fn event_stream(body: ByteStream) -> EventStream {
+    let state = SseState {
+        body,
+        decoder: SseDecoder::default(),
+        body_done: false,
+        failed: false,
+    };
+
+    Box::pin(stream::unfold(state, |mut state| async move {
+        if state.failed {
+            return None;
+        }
+
+        loop {
+            let event = if state.body_done {
+                state
+                    .decoder
+                    .next_event()
+                    .or_else(|| state.decoder.finish())
+            } else {
+                state.decoder.next_event()
+            };
+
+            if let Some(event) = event {
+                return Some((Ok(event), state));
+            }
+
+            if state.body_done {
+                return None;
+            }
+
+            match state.body.next().await {
+                Some(Ok(bytes)) => state.decoder.push(&bytes),
+                Some(Err(err)) => {
+                    state.failed = true;
+                    return Some((Err(err), state));
+                }
+                None => state.body_done = true,
+            }
+        }
+    }))
+}
+
+/// Turns SSE events into provider chunks using `parse`.
+///
+/// `parse` returns `Ok(None)` for events that carry nothing of interest. The first error
+/// ends the stream.
+pub(crate) fn chunk_stream<F>(events: EventStream, parse: F) -> ChunkStream
+where
+    F: FnMut(SseEvent) -> Result<Option<StreamChunk>> + Send + 'static,
+{
+    Box::pin(stream::unfold(
+        (events, parse, false),
+        |(mut events, mut parse, failed)| async move {
+            if failed {
+                return None;
+            }
+
+            loop {
+                let result = match events.next().await? {
+                    Ok(event) => match parse(event) {
+                        Ok(Some(chunk)) => Ok(chunk),
+                        Ok(None) => continue,
+                        Err(err) => Err(err),
+                    },
+                    Err(err) => Err(err),
+                };
+                let failed = result.is_err();
+                return Some((result, (events, parse, failed)));
+            }
+        },
+    ))
+}
+
+/// Tags SSE events with the provider that sent them.
+pub(crate) fn raw_event_stream(events: EventStream, provider: Provider) -> RawEventStream {
+    Box::pin(events.map(move |event| {
+        event.map(|event| RawEvent {
+            provider,
+            event: event.event,
+            data: event.data,
+        })
+    }))
+}
@@ -0,0 +1,275 @@
+//! Client-side rate limiting.
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// Order in which requests waiting for a [`RateLimiter`] are let through.
+///
+/// Set per request with [`LLMRequest::with_priority`](crate::LLMRequest::with_priority). It
+/// only matters while the client is throttled and is not sent to the provider.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Background work that may wait behind everything else
+    Low,
+    /// The default
+    #[default]
+    Normal,
+    /// Interactive requests that go before all others
+    High,
+}
+
+/// Delays requests so that a client stays below a number of requests and tokens per minute.
+///
+/// Each limit is a token bucket that holds up to one minute's worth and refills
+/// continuously, so short bursts go through immediately and sustained traffic is spread
+/// out. Requests that do not fit wait in a queue: the one with the highest [`Priority`],
+/// and among those the earliest, goes first as soon as the buckets can cover it. A request
+/// that costs more than a whole minute's worth waits for a full bucket instead of forever.
+///
+/// Usually set up through
+/// [`TurbineClient::with_rate_limit`](crate::TurbineClient::with_rate_limit).
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::rate_limit::RateLimiter;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// // 60000 tokens per minute refill at 1000 per second
+/// let limiter = RateLimiter::new(0, 60_000);
+/// assert!(limiter.acquire(60_000).await.is_zero());
+///
+/// let waited = limiter.acquire(50).await;
+/// assert!(waited >= std::time::Duration::from_millis(40));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+    /// Wakes waiters when the head of the queue may have changed
+    changed: Notify,
+}
+
+#[derive(Debug)]
+struct State {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    updated: Instant,
+    queue: Vec<Waiter>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    priority: Priority,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    per_second: f64,
+    available: f64,
+}
+
+impl Bucket {
+    fn per_minute(limit: u32) -> Option<Self> {
+        (limit > 0).then(|| Self {
+            capacity: f64::from(limit),
+            per_second: f64::from(limit) / 60.0,
+            available: f64::from(limit),
+        })
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.available =
+            (self.available + elapsed.as_secs_f64() * self.per_second).min(self.capacity);
+    }
+
+    /// Returns how long until the bucket holds `cost`.
+    fn wait_for(&self, cost: f64) -> Duration {
+        let missing = cost.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_second)
+        }
+    }
+
+    fn take(&mut self, cost: f64) {
+        self.available -= cost.min(self.capacity);
+    }
+}
+
+impl State {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated);
+        self.updated = now;
+        for bucket in [self.requests.as_mut(), self.tokens.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            bucket.refill(elapsed);
+        }
+    }
+
+    /// Returns how long until both buckets can cover a request costing `tokens`.
+    fn wait_for(&self, tokens: f64) -> Duration {
+        let requests = self.requests.as_ref().map(|bucket| bucket.wait_for(1.0));
+        let tokens = self.tokens.as_ref().map(|bucket| bucket.wait_for(tokens));
+        [requests, tokens]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn take(&mut self, tokens: f64) {
+        if let Some(bucket) = self.requests.as_mut() {
+            bucket.take(1.0);
+        }
+        if let Some(bucket) = self.tokens.as_mut() {
+            bucket.take(tokens);
+        }
+    }
+
+    /// Returns the waiter to serve next: highest priority first, then the earliest.
+    fn head(&self) -> Option<u64> {
+        self.queue
+            .iter()
+            .max_by_key(|waiter| (waiter.priority, std::cmp::Reverse(waiter.id)))
+            .map(|waiter| waiter.id)
+    }
+
+    fn remove(&mut self, id: u64) {
+        self.queue.retain(|waiter| waiter.id != id);
+    }
+}
+
+/// A place in the queue, given up when dropped (also when the waiting future is dropped).
+struct Ticket<'a> {
+    limiter: &'a RateLimiter,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.limiter
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(self.id);
+        self.limiter.changed.notify_waiters();
+    }
+}
+
+impl RateLimiter {
+    /// Creates a limiter for `requests_per_minute` and `tokens_per_minute`. A limit of 0
+    /// disables it.
+    pub fn new(requests_per_minute: u32, tokens_per_minute: u32) -> Self {
+        Self {
+            state: Mutex::new(State {
+                requests: Bucket::per_minute(requests_per_minute),
+                tokens: Bucket::per_minute(tokens_per_minute),
+                updated: Instant::now(),
+                queue: Vec::new(),
+                next_id: 0,
+            }),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Waits until a request costing `tokens` may be sent, and returns how long it waited.
+    ///
+    /// Same as [`acquire_with_priority`](Self::acquire_with_priority) with
+    /// [`Priority::Normal`].
+    pub async fn acquire(&self, tokens: u32) -> Duration {
+        self.acquire_with_priority(tokens, Priority::Normal).await
+    }
+
+    /// Waits until a request costing `tokens` may be sent, letting requests of higher
+    /// `priority` go first, and returns how long it waited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    /// use turbine_llm::rate_limit::{Priority, RateLimiter};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// // Refills at 1000 tokens per second, and the minute's tokens are used up
+    /// let limiter = Arc::new(RateLimiter::new(0, 60_000));
+    /// limiter.acquire(60_000).await;
+    /// let served = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let background = tokio::spawn({
+    ///     let (limiter, served) = (limiter.clone(), served.clone());
+    ///     async move {
+    ///         limiter.acquire_with_priority(500, Priority::Low).await;
+    ///         served.lock().unwrap().push(Priority::Low);
+    ///     }
+    /// });
+    /// tokio::time::sleep(Duration::from_millis(10)).await;
+    /// limiter.acquire_with_priority(50, Priority::High).await;
+    /// served.lock().unwrap().push(Priority::High);
+    /// background.await.unwrap();
+    ///
+    /// // The later interactive request was let through first
+    /// assert_eq!(*served.lock().unwrap(), [Priority::High, Priority::Low]);
+    /// # }
+    /// ```
+    pub async fn acquire_with_priority(&self, tokens: u32, priority: Priority) -> Duration {
+        let started = Instant::now();
+        let tokens = f64::from(tokens);
+        let ticket = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let id = state.next_id;
+            state.next_id += 1;
+            state.queue.push(Waiter { id, priority });
+            Ticket { limiter: self, id }
+        };
+
+        let mut waited = false;
+        loop {
+            // Registered before the state is checked, so no change is missed
+            let changed = self.changed.notified();
+            let wait = {
+                let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                state.refill();
+                if state.head() == Some(ticket.id) {
+                    let wait = state.wait_for(tokens);
+                    if wait.is_zero() {
+                        state.take(tokens);
+                        break;
+                    }
+                    Some(wait)
+                } else {
+                    None
+                }
+            };
+
+            waited = true;
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => changed.await,
+            }
+        }
+
+        drop(ticket);
+        if waited {
+            started.elapsed()
+        } else {
+            Duration::ZERO
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Retrying transient failures.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::error::{Result, TurbineError};
+
+/// How [`TurbineClient`](crate::TurbineClient) retries requests that fail with a
+/// retryable error (see [`TurbineError::is_retryable`](crate::TurbineError::is_retryable)).
+///
+/// The delay before retry `n` (starting at 0) is `initial_backoff * 2^n`, capped at
+/// `max_backoff`. Other errors are returned immediately. With a deadline, retrying also
+/// stops once the time spent so far plus the next delay would exceed it.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use turbine_llm::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy::new(5)
+///     .with_initial_backoff(Duration::from_millis(200))
+///     .with_max_backoff(Duration::from_secs(10));
+/// assert_eq!(policy.backoff(0), Duration::from_millis(200));
+/// assert_eq!(policy.backoff(2), Duration::from_millis(800));
+/// assert_eq!(policy.backoff(10), Duration::from_secs(10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between retries
+    pub max_backoff: Duration,
+    /// Limit on the total time spent on attempts and delays (`None` for no limit)
+    pub deadline: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that retries up to `max_retries` times, starting with a 500 ms
+    /// delay capped at 30 s.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            deadline: None,
+        }
+    }
+
+    /// Creates a policy that never retries.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the upper bound of the delay between retries.
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Stops retrying when the elapsed time plus the next delay would exceed `deadline`,
+    /// even if retries remain.
+    ///
+    /// The last error is then returned wrapped in [`TurbineError::DeadlineExceeded`]. The
+    /// deadline does not cut an attempt short; combine it with a request timeout for that.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use turbine_llm::providers::anthropic::AnthropicProvider;
+    /// use turbine_llm::retry::RetryPolicy;
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, TurbineClient, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(529, "overloaded")));
+    /// let policy = RetryPolicy::new(5)
+    ///     .with_initial_backoff(Duration::from_secs(2))
+    ///     .with_deadline(Duration::from_secs(1));
+    /// let client = TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(mock))
+    ///     .with_retry_policy(policy);
+    ///
+    /// // The first retry would start after the deadline, so the error comes back at once
+    /// let error = client.send_request(&LLMRequest::quick("claude-3-5-haiku-latest", "Hi")).await.unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     TurbineError::DeadlineExceeded { source, .. } if matches!(*source, TurbineError::Overloaded(_))
+    /// ));
+    /// # }
+    /// ```
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns the delay before retry `retry` (0 for the first retry).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    /// Runs `attempt` until it succeeds, fails with an error that is not retryable, or the
+    /// retries or the deadline are used up.
+    pub(crate) async fn run<T, F, Fut>(&self, mut attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let started = Instant::now();
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(error) if error.is_retryable() && retry < self.max_retries => {
+                    let backoff = self.backoff(retry);
+                    if let Some(deadline) = self.deadline
+                        && started.elapsed() + backoff > deadline
+                    {
+                        return Err(TurbineError::DeadlineExceeded {
+                            deadline,
+                            source: Box::new(error),
+                        });
+                    }
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Same as [`RetryPolicy::none`], the policy a [`TurbineClient`](crate::TurbineClient)
+    /// starts with.
+    ///
+    /// ```
+    /// use turbine_llm::retry::RetryPolicy;
+    ///
+    /// assert_eq!(RetryPolicy::default(), RetryPolicy::none());
+    /// ```
+    fn default() -> Self {
+        Self::none()
+    }
+}
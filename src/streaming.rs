@@ -0,0 +1,1189 @@
+//! Streaming responses.
+//!
+//! [`TurbineClient::send_request_stream`](crate::TurbineClient::send_request_stream) returns a
+//! [`ResponseStream`] that yields text deltas as they arrive and can be turned into the full
+//! [`LLMResponse`] at any point.
+//!
+//! # Buffering and backpressure
+//!
+//! Streams are pull-based: no background task reads the connection into a channel. The
+//! next network chunk is only read when the consumer polls the stream, so a slow consumer
+//! slows down reading and TCP flow control pushes back on the server. At most one network
+//! chunk plus one incomplete SSE event is buffered, in addition to the aggregated content
+//! that [`ResponseStream::into_response`] returns.
+//!
+//! ```
+//! use futures_util::{StreamExt, stream};
+//! use std::sync::Arc;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use turbine_llm::streaming::{ResponseStream, StreamChunk};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pulled = Arc::new(AtomicUsize::new(0));
+//! let counter = pulled.clone();
+//! let source = stream::iter(["a", "b", "c"]).map(move |text| {
+//!     counter.fetch_add(1, Ordering::SeqCst);
+//!     Ok(StreamChunk::delta(text))
+//! });
+//!
+//! let mut stream = ResponseStream::from_stream(source);
+//! assert_eq!(pulled.load(Ordering::SeqCst), 0);
+//!
+//! stream.next().await;
+//! // Only the chunk the consumer asked for was read
+//! assert_eq!(pulled.load(Ordering::SeqCst), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use futures_util::Stream;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+
+use crate::{
+    audit::AuditLogger,
+    error::{Result, TurbineError},
+    models::{LLMRequest, LLMResponse, ResponseMetrics, Usage},
+    types::{FinishReason, Provider},
+};
+
+/// An incremental piece of a streamed response.
+///
+/// Every chunk but the last carries a non-empty `delta`. The last chunk has `done` set, an
+/// empty `delta`, and the aggregated `usage` and `finish_reason` of the whole response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamChunk {
+    /// Newly generated text
+    pub delta: String,
+    /// Whether this is the terminal chunk
+    pub done: bool,
+    /// Token usage reported by the provider so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Why generation stopped, once known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// The model that generated the response, on the chunks where the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl StreamChunk {
+    /// Creates a chunk carrying a text delta.
+    pub fn delta(text: impl Into<String>) -> Self {
+        Self {
+            delta: text.into(),
+            done: false,
+            usage: None,
+            finish_reason: None,
+            model: None,
+        }
+    }
+}
+
+/// A boxed stream of provider chunks, as produced by a provider implementation.
+pub type ChunkStream = Pin<Box<dyn Stream<Item = Result<StreamChunk>> + Send>>;
+
+/// A server-sent event as a provider sent it, see
+/// [`TurbineClient::stream_raw_events`](crate::TurbineClient::stream_raw_events).
+///
+/// Events are passed on before any parsing, including those the crate ignores, so they show
+/// message boundaries and provider quirks that [`StreamChunk`]s hide. Use
+/// [`parse`](Self::parse) to get the provider's event as a [`ProviderEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawEvent {
+    /// The provider that sent the event
+    pub provider: Provider,
+    /// The `event:` field: the event type for Anthropic (`message_start`,
+    /// `content_block_delta`, `message_stop`, ...), `None` for the other providers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// The `data:` field, usually JSON; OpenAI and Groq end the stream with `[DONE]`
+    pub data: String,
+}
+
+impl RawEvent {
+    /// Parses the `data:` field as JSON.
+    pub fn json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::from_str(&self.data)?)
+    }
+
+    /// Parses the event into its provider's variant of [`ProviderEvent`].
+    ///
+    /// Events whose data is not JSON, other than the `[DONE]` marker, are returned as
+    /// [`ProviderEvent::Raw`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::streaming::{ProviderEvent, RawEvent};
+    /// use turbine_llm::Provider;
+    ///
+    /// let event = RawEvent {
+    ///     provider: Provider::Anthropic,
+    ///     event: Some("content_block_delta".to_string()),
+    ///     data: r#"{"type":"content_block_delta","delta":{"type":"text_delta","text":"Hi"}}"#.to_string(),
+    /// };
+    /// match event.parse() {
+    ///     ProviderEvent::Anthropic { event_type, data } => {
+    ///         assert_eq!(event_type, "content_block_delta");
+    ///         assert_eq!(data["delta"]["text"], "Hi");
+    ///     }
+    ///     other => panic!("unexpected event: {other:?}"),
+    /// }
+    ///
+    /// let done = RawEvent { provider: Provider::OpenAI, event: None, data: "[DONE]".to_string() };
+    /// assert_eq!(done.parse(), ProviderEvent::Done { provider: Provider::OpenAI });
+    /// ```
+    pub fn parse(&self) -> ProviderEvent {
+        if self.data == "[DONE]" {
+            return ProviderEvent::Done {
+                provider: self.provider,
+            };
+        }
+        let Ok(data) = self.json() else {
+            return ProviderEvent::Raw(self.clone());
+        };
+
+        match self.provider {
+            Provider::Anthropic => ProviderEvent::Anthropic {
+                event_type: self
+                    .event
+                    .clone()
+                    .or_else(|| data["type"].as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                data,
+            },
+            Provider::OpenAI => ProviderEvent::OpenAI(data),
+            Provider::Groq => ProviderEvent::Groq(data),
+            Provider::Gemini => ProviderEvent::Gemini(data),
+        }
+    }
+}
+
+/// A server-sent event parsed according to the provider that sent it, see
+/// [`RawEvent::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProviderEvent {
+    /// An Anthropic event with its type (`message_start`, `content_block_start`,
+    /// `content_block_delta`, `message_delta`, `message_stop`, `ping`, `error`, ...)
+    Anthropic {
+        /// The event type
+        event_type: String,
+        /// The event's JSON data
+        data: serde_json::Value,
+    },
+    /// An OpenAI chat completion chunk
+    OpenAI(serde_json::Value),
+    /// A Groq chat completion chunk, with usage under `x_groq` in the last one
+    Groq(serde_json::Value),
+    /// A Gemini `GenerateContentResponse` fragment
+    Gemini(serde_json::Value),
+    /// The `[DONE]` marker that ends OpenAI and Groq streams
+    Done {
+        /// The provider that sent the marker
+        provider: Provider,
+    },
+    /// An event whose data is not JSON, as received
+    Raw(RawEvent),
+}
+
+/// A boxed stream of [`RawEvent`]s.
+pub type RawEventStream = Pin<Box<dyn Stream<Item = Result<RawEvent>> + Send>>;
+
+/// Opens a new connection that continues after the given partial text.
+pub(crate) type Reconnector = Box<dyn Fn(String) -> BoxFuture<'static, Result<ChunkStream>> + Send>;
+
+/// A streamed response that yields text deltas and aggregates the final [`LLMResponse`].
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream;
+/// use turbine_llm::FinishReason;
+/// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+/// use turbine_llm::Usage;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut last = StreamChunk::delta("world!");
+/// last.usage = Some(Usage { input_tokens: 3, output_tokens: 2, ..Default::default() });
+/// last.finish_reason = Some(FinishReason::Stop);
+/// let mut stream = ResponseStream::new(Box::pin(stream::iter([
+///     Ok(StreamChunk::delta("Hello, ")),
+///     Ok(last),
+/// ])));
+///
+/// let first = stream.next().await.unwrap()?;
+/// assert_eq!(first.delta, "Hello, ");
+///
+/// // The rest of the stream is drained into the final response
+/// let response = stream.into_response().await?;
+/// assert_eq!(response.content, "Hello, world!");
+/// assert_eq!(response.usage.output_tokens, 2);
+/// assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ResponseStream {
+    inner: ChunkStream,
+    content: String,
+    usage: Usage,
+    finish_reason: Option<FinishReason>,
+    model: Option<String>,
+    finished: bool,
+    cancelled: Option<Pin<Box<WaitForCancellationFutureOwned>>>,
+    started: Instant,
+    time_to_first_token: Option<Duration>,
+    duration: Option<Duration>,
+    rate_limit_delay: Option<Duration>,
+    idle: Option<IdleTimer>,
+    thinking: Option<ThinkSplitter>,
+    reasoning: String,
+    reconnect: Option<Reconnect>,
+    warnings: Vec<String>,
+    /// Logger and request to record once the stream completes
+    audit: Option<(Arc<AuditLogger>, LLMRequest)>,
+    /// Whether the inner stream has ended and only the terminal chunk is left
+    inner_done: bool,
+}
+
+impl ResponseStream {
+    /// Wraps a stream of provider chunks.
+    ///
+    /// Provider chunks may carry partial usage; the latest non-zero value of each counter
+    /// wins. Chunks with an empty delta are folded into the aggregate without being yielded.
+    pub fn new(inner: ChunkStream) -> Self {
+        Self {
+            inner,
+            content: String::new(),
+            usage: Usage::default(),
+            finish_reason: None,
+            model: None,
+            finished: false,
+            cancelled: None,
+            started: Instant::now(),
+            time_to_first_token: None,
+            duration: None,
+            rate_limit_delay: None,
+            idle: None,
+            thinking: None,
+            reasoning: String::new(),
+            reconnect: None,
+            warnings: Vec::new(),
+            audit: None,
+            inner_done: false,
+        }
+    }
+
+    /// Writes `request` and the complete response to `logger` when the stream finishes.
+    /// Streams that fail or are cancelled are not logged.
+    pub(crate) fn with_audit_logger(
+        mut self,
+        logger: Arc<AuditLogger>,
+        request: LLMRequest,
+    ) -> Self {
+        self.audit = Some((logger, request));
+        self
+    }
+
+    /// Records how long the request waited for the client's rate limit.
+    pub(crate) fn with_rate_limit_delay(mut self, delay: Option<Duration>) -> Self {
+        self.rate_limit_delay = delay;
+        self
+    }
+
+    /// Replaces a connection that fails with a retryable error, up to `max_attempts` times,
+    /// by one opened with `connect`, which receives the text received so far.
+    pub(crate) fn with_reconnect(mut self, max_attempts: u32, connect: Reconnector) -> Self {
+        self.reconnect = Some(Reconnect {
+            attempts_left: max_attempts,
+            connect,
+            pending: None,
+            reason: String::new(),
+        });
+        self
+    }
+
+    /// Measures time to first token from `started` instead of from when the stream was
+    /// created, e.g. from when the request was sent.
+    pub(crate) fn with_started_at(mut self, started: Instant) -> Self {
+        self.started = started;
+        self
+    }
+
+    /// Returns the time from sending the request to the first non-empty delta, once it has
+    /// arrived.
+    ///
+    /// Streams from [`TurbineClient`](crate::TurbineClient) measure from just before the
+    /// request is sent; other streams measure from when they were created.
+    pub fn time_to_first_token(&self) -> Option<Duration> {
+        self.time_to_first_token
+    }
+
+    /// Moves `<think>...</think>` blocks out of the text into
+    /// [`LLMResponse::reasoning`].
+    ///
+    /// Deltas only carry the answer. A tag split across chunks is recognized: text that may
+    /// be the start of a tag is held back until the next chunk shows whether it is one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::stream;
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let chunks = ["<think>The user gre", "ets me.</th", "ink>\n\nHel", "lo!"];
+    /// let mut stream = ResponseStream::from_stream(stream::iter(
+    ///     chunks.map(|chunk| Ok(StreamChunk::delta(chunk))),
+    /// ))
+    /// .with_extract_thinking();
+    ///
+    /// assert_eq!(stream.next().await.unwrap()?.delta, "Hel");
+    /// assert_eq!(stream.reasoning(), "The user greets me.");
+    ///
+    /// let response = stream.into_response().await?;
+    /// assert_eq!(response.content, "Hello!");
+    /// assert_eq!(response.reasoning.as_deref(), Some("The user greets me."));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_extract_thinking(mut self) -> Self {
+        self.thinking = Some(ThinkSplitter::default());
+        self
+    }
+
+    /// Returns the reasoning extracted so far by
+    /// [`with_extract_thinking`](Self::with_extract_thinking).
+    pub fn reasoning(&self) -> &str {
+        &self.reasoning
+    }
+
+    /// Stops the stream when `cancel` is cancelled.
+    ///
+    /// After cancellation the stream yields a single [`TurbineError::Cancelled`] and ends,
+    /// dropping the underlying connection. The text and usage received until then remain
+    /// available through [`partial_response`](Self::partial_response).
+    /// [`into_response`](Self::into_response) returns them in
+    /// [`TurbineError::StreamInterrupted`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::stream;
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::{CancellationToken, TurbineError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let cancel = CancellationToken::new();
+    /// let mut stream = ResponseStream::from_stream(stream::iter([
+    ///     Ok(StreamChunk::delta("Hello")),
+    ///     Ok(StreamChunk::delta(" world")),
+    /// ]))
+    /// .with_cancellation(cancel.clone());
+    ///
+    /// assert_eq!(stream.next().await.unwrap()?.delta, "Hello");
+    /// cancel.cancel();
+    /// assert!(matches!(stream.next().await, Some(Err(TurbineError::Cancelled))));
+    /// assert!(stream.next().await.is_none());
+    /// assert_eq!(stream.content(), "Hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancelled = Some(Box::pin(cancel.cancelled_owned()));
+        self
+    }
+
+    /// Fails the stream when no chunk arrives for `limit` after the previous one.
+    ///
+    /// The wait for the first chunk is not limited. A stalled stream yields
+    /// [`TurbineError::Timeout`] with reason `"idle"` (wrapped in
+    /// [`TurbineError::StreamInterrupted`] if text had arrived) and ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use futures_util::{StreamExt, stream};
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::TurbineError;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// // The server sends one chunk and then hangs
+    /// let stalled = stream::iter([Ok(StreamChunk::delta("Hello"))]).chain(stream::pending());
+    /// let stream = ResponseStream::from_stream(stalled).with_idle_timeout(Duration::from_millis(50));
+    ///
+    /// match stream.into_response().await {
+    ///     Err(TurbineError::StreamInterrupted { partial, source, .. }) => {
+    ///         assert_eq!(partial, "Hello");
+    ///         assert!(matches!(*source, TurbineError::Timeout { reason, .. } if reason == "idle"));
+    ///     }
+    ///     other => panic!("unexpected result: {other:?}"),
+    /// }
+    /// # }
+    /// ```
+    pub fn with_idle_timeout(mut self, limit: Duration) -> Self {
+        self.idle = Some(IdleTimer { limit, sleep: None });
+        self
+    }
+
+    /// Wraps any stream of provider chunks, boxing it.
+    ///
+    /// This is how custom providers plug a non-SSE source (a WebSocket, a channel, ...)
+    /// into [`LLMProviderTrait::stream_request`](crate::providers::LLMProviderTrait::stream_request).
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<StreamChunk>> + Send + 'static,
+    {
+        Self::new(Box::pin(stream))
+    }
+
+    /// Returns the next chunk, or `None` once the terminal chunk has been returned.
+    pub async fn next(&mut self) -> Option<Result<StreamChunk>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Returns the text received so far.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the warnings collected so far, such as reconnects after a dropped
+    /// connection. [`into_response`](Self::into_response) moves them into
+    /// [`LLMResponse::warnings`].
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Parses the streamed text as JSON, yielding values as soon as they are complete.
+    ///
+    /// See [`JsonStream`].
+    pub fn json<T: DeserializeOwned>(self) -> JsonStream<T> {
+        JsonStream {
+            inner: self,
+            splitter: JsonSplitter::default(),
+            pending: VecDeque::new(),
+            finished: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Consumes the rest of the stream and returns the assembled response.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error the stream produced. If the stream fails after text has
+    /// arrived, e.g. because the connection dropped, the error is
+    /// [`TurbineError::StreamInterrupted`] carrying that text and the usage reported so far;
+    /// errors before the first text are returned as is. Cancellation through
+    /// [`with_cancellation`](Self::with_cancellation) is always returned as
+    /// [`TurbineError::StreamInterrupted`] with [`TurbineError::Cancelled`] as its source, so
+    /// the tokens already generated can be accounted for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::stream;
+    /// use std::io::{Error, ErrorKind};
+    /// use turbine_llm::TurbineError;
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// // The connection drops after two chunks
+    /// let stream = ResponseStream::from_stream(stream::iter([
+    ///     Ok(StreamChunk::delta("Once upon ")),
+    ///     Ok(StreamChunk::delta("a time")),
+    ///     Err(TurbineError::IoError(Error::new(ErrorKind::ConnectionReset, "connection reset"))),
+    /// ]));
+    ///
+    /// match stream.into_response().await {
+    ///     Err(TurbineError::StreamInterrupted { partial, source, .. }) => {
+    ///         assert_eq!(partial, "Once upon a time");
+    ///         assert!(matches!(*source, TurbineError::IoError(_)));
+    ///     }
+    ///     other => panic!("unexpected result: {other:?}"),
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// A cancelled stream keeps its usage:
+    ///
+    /// ```
+    /// use futures_util::{StreamExt, stream};
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::{CancellationToken, TurbineError, Usage};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut start = StreamChunk::delta("Once upon");
+    /// start.usage = Some(Usage { input_tokens: 120, ..Default::default() });
+    /// let chunks = stream::iter([Ok(start)]).chain(stream::pending());
+    ///
+    /// let cancel = CancellationToken::new();
+    /// let stream = ResponseStream::from_stream(chunks).with_cancellation(cancel.clone());
+    /// tokio::spawn(async move { cancel.cancel() });
+    ///
+    /// let error = stream.into_response().await.unwrap_err();
+    /// assert_eq!(error.usage().unwrap().input_tokens, 120);
+    /// assert!(matches!(
+    ///     error,
+    ///     TurbineError::StreamInterrupted { partial, source, .. }
+    ///         if partial == "Once upon" && matches!(*source, TurbineError::Cancelled)
+    /// ));
+    /// # }
+    /// ```
+    pub async fn into_response(mut self) -> Result<LLMResponse> {
+        while let Some(chunk) = self.next().await {
+            match chunk {
+                Err(TurbineError::Cancelled) => {
+                    return Err(TurbineError::StreamInterrupted {
+                        partial: self.content.clone(),
+                        usage: self.usage.clone(),
+                        source: Box::new(TurbineError::Cancelled),
+                    });
+                }
+                chunk => {
+                    chunk?;
+                }
+            }
+        }
+        Ok(self.partial_response())
+    }
+
+    /// Returns the usage reported by the provider so far.
+    ///
+    /// Providers differ in when they report it: Anthropic sends the input tokens up front
+    /// and Gemini sends running totals, while OpenAI and Groq report only at the end. Until
+    /// then the counts are zero.
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    /// Returns the response assembled from what has arrived so far, without reading
+    /// further.
+    ///
+    /// Use it after cancellation or an error to account for the tokens generated until then:
+    /// `usage` holds whatever the provider had reported (see [`usage`](Self::usage)), and
+    /// `finish_reason` is `None` if the stream did not finish.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::{StreamExt, stream};
+    /// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+    /// use turbine_llm::{CancellationToken, TurbineError, Usage};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Like Anthropic, the provider reports the input tokens with the first event
+    /// let mut start = StreamChunk::delta("");
+    /// start.usage = Some(Usage { input_tokens: 120, ..Default::default() });
+    /// let chunks = stream::iter([Ok(start), Ok(StreamChunk::delta("Once upon"))]).chain(stream::pending());
+    ///
+    /// let cancel = CancellationToken::new();
+    /// let mut stream = ResponseStream::from_stream(chunks).with_cancellation(cancel.clone());
+    /// stream.next().await.unwrap()?;
+    /// cancel.cancel();
+    /// assert!(matches!(stream.next().await, Some(Err(TurbineError::Cancelled))));
+    ///
+    /// let partial = stream.partial_response();
+    /// assert_eq!(partial.content, "Once upon");
+    /// assert_eq!(partial.usage.input_tokens, 120);
+    /// assert_eq!(partial.finish_reason, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn partial_response(&self) -> LLMResponse {
+        let mut response = LLMResponse::new(
+            self.content.clone(),
+            self.usage.input_tokens,
+            self.usage.output_tokens,
+        );
+        response.usage = self.usage.clone();
+        response.finish_reason = self.finish_reason.clone();
+        response.model = self.model.clone().unwrap_or_default();
+        response.warnings = self.warnings.clone();
+        let reasoning = self.reasoning.trim();
+        if !reasoning.is_empty() {
+            response.reasoning = Some(reasoning.to_string());
+        }
+        let window = self
+            .duration
+            .zip(self.time_to_first_token)
+            .map(|(duration, first)| duration.saturating_sub(first));
+        response.metrics = Some(ResponseMetrics {
+            time_to_first_token: self.time_to_first_token,
+            duration: self.duration,
+            rate_limit_delay: self.rate_limit_delay,
+            tokens_per_second: window
+                .and_then(|window| ResponseMetrics::throughput(self.usage.output_tokens, window)),
+        });
+        response
+    }
+
+    fn absorb(&mut self, chunk: &StreamChunk) {
+        self.content.push_str(&chunk.delta);
+        if let Some(usage) = &chunk.usage {
+            if usage.input_tokens > 0 {
+                self.usage.input_tokens = usage.input_tokens;
+            }
+            if usage.output_tokens > 0 {
+                self.usage.output_tokens = usage.output_tokens;
+            }
+            if usage.cached_input_tokens.is_some() {
+                self.usage.cached_input_tokens = usage.cached_input_tokens;
+            }
+            if usage.reasoning_tokens.is_some() {
+                self.usage.reasoning_tokens = usage.reasoning_tokens;
+            }
+        }
+        if chunk.finish_reason.is_some() {
+            self.finish_reason = chunk.finish_reason.clone();
+        }
+        if self.model.is_none() {
+            self.model = chunk.model.clone();
+        }
+    }
+
+    /// Starts reconnecting after `err` if it is retryable and attempts are left.
+    fn try_reconnect(&mut self, err: &TurbineError) -> bool {
+        let Some(reconnect) = self.reconnect.as_mut() else {
+            return false;
+        };
+        if reconnect.attempts_left == 0 || !is_dropped(err) {
+            return false;
+        }
+        reconnect.attempts_left -= 1;
+        reconnect.reason = err.to_string();
+        reconnect.pending = Some((reconnect.connect)(self.content.clone()));
+        true
+    }
+
+    /// Wraps a provider error in [`TurbineError::StreamInterrupted`] if text has already
+    /// arrived.
+    fn interrupted(&self, err: TurbineError) -> TurbineError {
+        if self.content.is_empty() {
+            return err;
+        }
+        TurbineError::StreamInterrupted {
+            partial: self.content.clone(),
+            usage: self.usage.clone(),
+            source: Box::new(err),
+        }
+    }
+
+    fn terminal_chunk(&self) -> StreamChunk {
+        StreamChunk {
+            delta: String::new(),
+            done: true,
+            usage: Some(self.usage.clone()),
+            finish_reason: self.finish_reason.clone(),
+            model: self.model.clone(),
+        }
+    }
+}
+
+impl Stream for ResponseStream {
+    type Item = Result<StreamChunk>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        if let Some(cancelled) = this.cancelled.as_mut()
+            && cancelled.as_mut().poll(cx).is_ready()
+        {
+            this.finished = true;
+            return Poll::Ready(Some(Err(TurbineError::Cancelled)));
+        }
+
+        loop {
+            if let Some(reconnect) = this.reconnect.as_mut()
+                && let Some(pending) = reconnect.pending.as_mut()
+            {
+                let Poll::Ready(result) = pending.as_mut().poll(cx) else {
+                    return Poll::Pending;
+                };
+                reconnect.pending = None;
+                match result {
+                    Ok(inner) => {
+                        this.warnings.push(format!(
+                            "Stream reconnected after an error: {}",
+                            reconnect.reason
+                        ));
+                        this.inner = inner;
+                        if let Some(idle) = this.idle.as_mut() {
+                            idle.sleep = None;
+                        }
+                    }
+                    Err(err) => {
+                        if this.try_reconnect(&err) {
+                            continue;
+                        }
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(this.interrupted(err))));
+                    }
+                }
+            }
+
+            if this.inner_done {
+                this.finished = true;
+                if let Some((logger, request)) = this.audit.take()
+                    && let Err(error) = logger.log(&request, &this.partial_response())
+                {
+                    this.warnings
+                        .push(format!("Could not write the audit log: {}", error));
+                }
+                return Poll::Ready(Some(Ok(this.terminal_chunk())));
+            }
+
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Pending => {
+                    if let Some(idle) = this.idle.as_mut()
+                        && idle.expired(cx)
+                    {
+                        let err = TurbineError::Timeout {
+                            reason: "idle".to_string(),
+                            limit: idle.limit,
+                        };
+                        if this.try_reconnect(&err) {
+                            continue;
+                        }
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(this.interrupted(err))));
+                    }
+                    return Poll::Pending;
+                }
+                Poll::Ready(Some(Ok(mut chunk))) => {
+                    if let Some(idle) = this.idle.as_mut() {
+                        idle.restart();
+                    }
+                    if let Some(thinking) = this.thinking.as_mut() {
+                        let (reasoning, answer) = thinking.push(&chunk.delta);
+                        this.reasoning.push_str(&reasoning);
+                        chunk.delta = answer;
+                    }
+                    this.absorb(&chunk);
+                    if !chunk.delta.is_empty() {
+                        this.time_to_first_token
+                            .get_or_insert_with(|| this.started.elapsed());
+                        return Poll::Ready(Some(Ok(StreamChunk {
+                            done: false,
+                            ..chunk
+                        })));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    if this.try_reconnect(&err) {
+                        continue;
+                    }
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(this.interrupted(err))));
+                }
+                Poll::Ready(None) => {
+                    this.inner_done = true;
+                    this.duration = Some(this.started.elapsed());
+                    // Text held back as a possible tag start turned out not to be one
+                    if let Some(thinking) = this.thinking.as_mut() {
+                        let (reasoning, answer) = thinking.finish();
+                        this.reasoning.push_str(&reasoning);
+                        if !answer.is_empty() {
+                            let chunk = StreamChunk::delta(answer);
+                            this.absorb(&chunk);
+                            return Poll::Ready(Some(Ok(chunk)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ResponseStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseStream")
+            .field("content", &self.content)
+            .field("usage", &self.usage)
+            .field("finish_reason", &self.finish_reason)
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A stream of JSON values parsed incrementally from a [`ResponseStream`].
+///
+/// If the response is a top-level array, each element is yielded as soon as it is complete,
+/// so list items can be rendered while the rest of the array is still being generated.
+/// Otherwise the top-level object is yielded once it is complete. Text before the first
+/// `[` or `{` (such as a Markdown code fence) and after the closing bracket is ignored.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream;
+/// use serde::Deserialize;
+/// use turbine_llm::streaming::{ResponseStream, StreamChunk};
+///
+/// #[derive(Debug, Deserialize, PartialEq)]
+/// struct City {
+///     name: String,
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let chunks = ["[{\"name\": \"Par", "is\"}, {\"name\"", ": \"Rome\"}]"];
+/// let response = ResponseStream::new(Box::pin(stream::iter(
+///     chunks.map(|chunk| Ok(StreamChunk::delta(chunk))),
+/// )));
+///
+/// let mut cities = response.json::<City>();
+/// assert_eq!(cities.next().await.unwrap()?, City { name: "Paris".to_string() });
+/// assert_eq!(cities.next().await.unwrap()?, City { name: "Rome".to_string() });
+/// assert!(cities.next().await.is_none());
+/// # Ok(())
+/// # }
+/// ```
+pub struct JsonStream<T> {
+    inner: ResponseStream,
+    splitter: JsonSplitter,
+    pending: VecDeque<Result<T>>,
+    finished: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// Values are only ever moved out of `pending`, never pinned
+impl<T> Unpin for JsonStream<T> {}
+
+impl<T: DeserializeOwned> JsonStream<T> {
+    /// Returns the next value, or `None` when the response is exhausted.
+    pub async fn next(&mut self) -> Option<Result<T>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Returns the text received so far.
+    pub fn content(&self) -> &str {
+        self.inner.content()
+    }
+
+    fn queue(&mut self, values: Vec<String>) {
+        self.pending.extend(
+            values
+                .iter()
+                .map(|value| serde_json::from_str(value).map_err(TurbineError::from)),
+        );
+    }
+}
+
+impl<T: DeserializeOwned> Stream for JsonStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(value) = this.pending.pop_front() {
+                return Poll::Ready(Some(value));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(chunk))) if !chunk.done => {
+                    let values = this.splitter.push(&chunk.delta);
+                    this.queue(values);
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                Poll::Ready(Some(Ok(_))) | Poll::Ready(None) => {
+                    this.finished = true;
+                    if let Err(err) = this.splitter.finish() {
+                        this.pending.push_back(Err(err));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for JsonStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonStream")
+            .field("inner", &self.inner)
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Separates `<think>...</think>` blocks from the answer.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::streaming::split_thinking;
+///
+/// let (reasoning, answer) = split_thinking("<think>2 + 2 is 4.</think>\n\nThe answer is 4.");
+/// assert_eq!(reasoning.as_deref(), Some("2 + 2 is 4."));
+/// assert_eq!(answer, "The answer is 4.");
+///
+/// assert_eq!(split_thinking("No reasoning here"), (None, "No reasoning here".to_string()));
+/// ```
+pub fn split_thinking(text: &str) -> (Option<String>, String) {
+    let mut splitter = ThinkSplitter::default();
+    let (mut reasoning, mut answer) = splitter.push(text);
+    let (rest_reasoning, rest_answer) = splitter.finish();
+    reasoning.push_str(&rest_reasoning);
+    answer.push_str(&rest_answer);
+
+    let reasoning = reasoning.trim();
+    (
+        (!reasoning.is_empty()).then(|| reasoning.to_string()),
+        answer,
+    )
+}
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Returns whether a stream may go on over a new connection after `err`: the error is
+/// retryable, or the connection failed while reading the body.
+fn is_dropped(err: &TurbineError) -> bool {
+    match err {
+        TurbineError::IoError(_) => true,
+        TurbineError::HttpError(error) if error.is_body() || error.is_request() => true,
+        TurbineError::StreamInterrupted { source, .. } => is_dropped(source),
+        _ => err.is_retryable(),
+    }
+}
+
+/// Reconnection state of a [`ResponseStream`].
+struct Reconnect {
+    attempts_left: u32,
+    connect: Reconnector,
+    /// The connection being opened, if any
+    pending: Option<BoxFuture<'static, Result<ChunkStream>>>,
+    /// The error that caused the current reconnect
+    reason: String,
+}
+
+/// Limits the gap between two chunks of a [`ResponseStream`].
+struct IdleTimer {
+    limit: Duration,
+    /// Deadline for the next chunk; `None` until the first chunk arrived
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl IdleTimer {
+    /// Starts waiting for the next chunk.
+    fn restart(&mut self) {
+        let deadline = tokio::time::Instant::now() + self.limit;
+        match self.sleep.as_mut() {
+            Some(sleep) => sleep.as_mut().reset(deadline),
+            None => self.sleep = Some(Box::pin(tokio::time::sleep_until(deadline))),
+        }
+    }
+
+    /// Returns whether the deadline has passed, registering `cx` to be woken at it if not.
+    fn expired(&mut self, cx: &mut Context<'_>) -> bool {
+        self.sleep
+            .as_mut()
+            .is_some_and(|sleep| sleep.as_mut().poll(cx).is_ready())
+    }
+}
+
+/// Splits partial text into reasoning inside think tags and the answer outside them.
+#[derive(Debug, Default)]
+struct ThinkSplitter {
+    inside: bool,
+    /// Text not yet classified because it may be the start of a tag
+    pending: String,
+    /// Whether whitespace right after a closing tag is still being skipped
+    after_close: bool,
+}
+
+impl ThinkSplitter {
+    /// Appends text and returns the reasoning and answer it completed.
+    fn push(&mut self, text: &str) -> (String, String) {
+        self.pending.push_str(text);
+        let mut reasoning = String::new();
+        let mut answer = String::new();
+
+        loop {
+            let tag = if self.inside { THINK_CLOSE } else { THINK_OPEN };
+            let (end, rest) = match self.pending.find(tag) {
+                Some(index) => (index, index + tag.len()),
+                None => {
+                    // Hold back a suffix that may be the start of the tag
+                    let keep = (1..tag.len())
+                        .rev()
+                        .find(|len| self.pending.ends_with(&tag[..*len]))
+                        .unwrap_or(0);
+                    let end = self.pending.len() - keep;
+                    (end, end)
+                }
+            };
+
+            let text = self.pending[..end].to_string();
+            self.pending.drain(..rest);
+            self.emit(&text, &mut reasoning, &mut answer);
+            if end == rest {
+                return (reasoning, answer);
+            }
+            self.inside = !self.inside;
+            self.after_close = !self.inside;
+        }
+    }
+
+    /// Returns the text still held back.
+    fn finish(&mut self) -> (String, String) {
+        let text = std::mem::take(&mut self.pending);
+        let mut reasoning = String::new();
+        let mut answer = String::new();
+        self.emit(&text, &mut reasoning, &mut answer);
+        (reasoning, answer)
+    }
+
+    fn emit(&mut self, text: &str, reasoning: &mut String, answer: &mut String) {
+        if self.inside {
+            reasoning.push_str(text);
+            return;
+        }
+
+        let text = if self.after_close {
+            let trimmed = text.trim_start();
+            self.after_close = trimmed.is_empty();
+            trimmed
+        } else {
+            text
+        };
+        answer.push_str(text);
+    }
+}
+
+/// Splits partial JSON text into complete top-level values.
+#[derive(Debug, Default)]
+struct JsonSplitter {
+    buffer: String,
+    /// Next byte of `buffer` to scan
+    position: usize,
+    /// Whether the top-level value is an array whose elements are emitted one by one
+    array: bool,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Start of the value currently being read
+    value_start: Option<usize>,
+    done: bool,
+}
+
+impl JsonSplitter {
+    /// Appends text and returns the values it completed.
+    fn push(&mut self, text: &str) -> Vec<String> {
+        self.buffer.push_str(text);
+        let mut values = Vec::new();
+
+        // Structural characters are ASCII, so scanning bytes is safe with UTF-8 input
+        while self.position < self.buffer.len() && !self.done {
+            let index = self.position;
+            let byte = self.buffer.as_bytes()[index];
+            self.position += 1;
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if self.depth == 0 {
+                // Skip anything before the top-level value
+                if byte == b'[' || byte == b'{' {
+                    self.array = byte == b'[';
+                    self.depth = 1;
+                    if !self.array {
+                        self.value_start = Some(index);
+                    }
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    self.start_element(index);
+                }
+                b'[' | b'{' => {
+                    self.start_element(index);
+                    self.depth += 1;
+                }
+                b']' | b'}' => {
+                    if self.array && self.depth == 1 {
+                        // End of the top-level array; emit a trailing scalar element
+                        self.take_value(index, &mut values);
+                    }
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        if !self.array {
+                            self.take_value(index + 1, &mut values);
+                        }
+                        self.done = true;
+                    } else if self.array && self.depth == 1 {
+                        self.take_value(index + 1, &mut values);
+                    }
+                }
+                b',' if self.array && self.depth == 1 => self.take_value(index, &mut values),
+                byte if !byte.is_ascii_whitespace() => self.start_element(index),
+                _ => {}
+            }
+        }
+
+        values
+    }
+
+    /// Checks that the top-level value was complete.
+    fn finish(&self) -> Result<()> {
+        if self.done {
+            Ok(())
+        } else {
+            Err(TurbineError::InvalidResponse(format!(
+                "Streamed response ended before the JSON value was complete: {}",
+                self.buffer
+            )))
+        }
+    }
+
+    fn start_element(&mut self, index: usize) {
+        if self.array && self.depth == 1 && self.value_start.is_none() {
+            self.value_start = Some(index);
+        }
+    }
+
+    fn take_value(&mut self, end: usize, values: &mut Vec<String>) {
+        if let Some(start) = self.value_start.take() {
+            values.push(self.buffer[start..end].trim().to_string());
+        }
+    }
+}
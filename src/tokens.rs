@@ -0,0 +1,202 @@
+//! Token estimation helpers.
+//!
+//! Estimates use a character-based heuristic (about four characters per token for English
+//! text). They are meant for budgeting decisions such as trimming history, not for billing.
+//!
+//! For exact counts, implement [`Tokenizer`] (e.g. on top of `tiktoken-rs`) and register it
+//! with [`TurbineClient::with_tokenizer`](crate::TurbineClient::with_tokenizer) or pass it to
+//! [`estimate_request_tokens_with`].
+//!
+//! With the `tiktoken` feature, `BpeTokenizer` counts exactly with OpenAI's BPE encodings,
+//! and the client uses it by default for the OpenAI models `tiktoken-rs` knows.
+
+use crate::models::{LLMRequest, Message};
+
+/// Average number of characters per token used by the heuristic.
+const CHARS_PER_TOKEN: u32 = 4;
+
+/// Fixed overhead per message for role markers and separators.
+const TOKENS_PER_MESSAGE: u32 = 4;
+
+/// Rough cost of a single image or document part.
+const TOKENS_PER_CONTENT_PART: u32 = 1_000;
+
+/// Counts the tokens of a text for a family of models.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::tokens::{Tokenizer, estimate_request_tokens_with};
+/// use turbine_llm::LLMRequest;
+///
+/// /// Counts one token per whitespace-separated word.
+/// struct WordTokenizer;
+///
+/// impl Tokenizer for WordTokenizer {
+///     fn count(&self, text: &str) -> usize {
+///         text.split_whitespace().count()
+///     }
+/// }
+///
+/// let request = LLMRequest::quick("my-model", "one two three");
+/// // 3 words plus the per-message overhead
+/// assert_eq!(estimate_request_tokens_with(&request, &WordTokenizer), 7);
+/// ```
+pub trait Tokenizer: Send + Sync {
+    /// Returns the number of tokens in `text`.
+    fn count(&self, text: &str) -> usize;
+}
+
+/// The character-based heuristic, used when no tokenizer is registered for a model.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        estimate_tokens(text) as usize
+    }
+}
+
+/// Exact token counts with one of OpenAI's BPE encodings, from `tiktoken-rs`.
+///
+/// Requires the `tiktoken` feature. [`TurbineClient`](crate::TurbineClient) uses it for
+/// OpenAI models without a registered tokenizer; models `tiktoken-rs` does not know keep the
+/// heuristic.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::tokens::{BpeTokenizer, Tokenizer};
+/// use turbine_llm::{Provider, TurbineClient};
+///
+/// let tokenizer = BpeTokenizer::for_model("gpt-4o-mini").unwrap();
+/// assert_eq!(tokenizer.count("Hello, world!"), 4);
+/// assert!(BpeTokenizer::for_model("claude-3-5-haiku-latest").is_none());
+///
+/// // The default for OpenAI models
+/// let client = TurbineClient::new_with_key(Provider::OpenAI, "sk-test");
+/// assert_eq!(client.count_tokens("gpt-4o", "tokenization"), 2);
+/// ```
+#[cfg(feature = "tiktoken")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BpeTokenizer {
+    encoding: tiktoken_rs::tokenizer::Tokenizer,
+}
+
+#[cfg(feature = "tiktoken")]
+impl BpeTokenizer {
+    /// The `o200k_base` encoding of GPT-4o, GPT-4.1 and the o-series models.
+    pub const O200K_BASE: Self = Self::new(tiktoken_rs::tokenizer::Tokenizer::O200kBase);
+
+    /// The `cl100k_base` encoding of GPT-4 and GPT-3.5 Turbo.
+    pub const CL100K_BASE: Self = Self::new(tiktoken_rs::tokenizer::Tokenizer::Cl100kBase);
+
+    const P50K_BASE: Self = Self::new(tiktoken_rs::tokenizer::Tokenizer::P50kBase);
+    const P50K_EDIT: Self = Self::new(tiktoken_rs::tokenizer::Tokenizer::P50kEdit);
+    const R50K_BASE: Self = Self::new(tiktoken_rs::tokenizer::Tokenizer::R50kBase);
+
+    const fn new(encoding: tiktoken_rs::tokenizer::Tokenizer) -> Self {
+        Self { encoding }
+    }
+
+    /// Returns the tokenizer of an OpenAI model, or `None` if `tiktoken-rs` does not know
+    /// the model.
+    pub fn for_model(model: &str) -> Option<&'static Self> {
+        use tiktoken_rs::tokenizer::{Tokenizer as Encoding, get_tokenizer};
+
+        Some(match get_tokenizer(model)? {
+            Encoding::O200kBase => &Self::O200K_BASE,
+            Encoding::Cl100kBase => &Self::CL100K_BASE,
+            Encoding::P50kBase => &Self::P50K_BASE,
+            Encoding::P50kEdit => &Self::P50K_EDIT,
+            Encoding::R50kBase | Encoding::Gpt2 => &Self::R50K_BASE,
+        })
+    }
+
+    fn bpe(&self) -> &'static tiktoken_rs::CoreBPE {
+        use tiktoken_rs::tokenizer::Tokenizer as Encoding;
+
+        // The encodings are loaded on first use and shared afterwards
+        match self.encoding {
+            Encoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Encoding::P50kBase => tiktoken_rs::p50k_base_singleton(),
+            Encoding::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+            Encoding::R50kBase | Encoding::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+        }
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for BpeTokenizer {
+    fn count(&self, text: &str) -> usize {
+        self.bpe().encode_with_special_tokens(text).len()
+    }
+}
+
+/// Returns the tokenizer used for `model` when none is registered.
+#[cfg(feature = "tiktoken")]
+pub(crate) fn default_tokenizer(model: &str) -> &'static dyn Tokenizer {
+    match BpeTokenizer::for_model(model) {
+        Some(tokenizer) => tokenizer,
+        None => &HeuristicTokenizer,
+    }
+}
+
+/// Returns the tokenizer used for `model` when none is registered.
+#[cfg(not(feature = "tiktoken"))]
+pub(crate) fn default_tokenizer(_model: &str) -> &'static dyn Tokenizer {
+    &HeuristicTokenizer
+}
+
+/// Estimates the number of tokens in `text`.
+///
+/// # Example
+///
+/// ```
+/// use turbine_llm::tokens::estimate_tokens;
+///
+/// assert_eq!(estimate_tokens(""), 0);
+/// assert_eq!(estimate_tokens("Hello, world!"), 4);
+/// ```
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Estimates the number of tokens a single message contributes to the prompt.
+pub fn estimate_message_tokens(message: &Message) -> u32 {
+    message_tokens(message, &HeuristicTokenizer)
+}
+
+/// Estimates the number of input tokens of a request: system prompt plus all messages.
+pub fn estimate_request_tokens(request: &LLMRequest) -> u32 {
+    estimate_request_tokens_with(request, &HeuristicTokenizer)
+}
+
+/// Like [`estimate_request_tokens`], but counts text with `tokenizer`.
+///
+/// Per-message overhead and image or document parts are still estimated.
+pub fn estimate_request_tokens_with(request: &LLMRequest, tokenizer: &dyn Tokenizer) -> u32 {
+    let system = request
+        .system_prompt
+        .as_deref()
+        .map(|prompt| TOKENS_PER_MESSAGE.saturating_add(saturate(tokenizer.count(prompt))))
+        .unwrap_or(0);
+
+    request
+        .messages
+        .iter()
+        .map(|message| message_tokens(message, tokenizer))
+        .fold(system, u32::saturating_add)
+}
+
+fn message_tokens(message: &Message, tokenizer: &dyn Tokenizer) -> u32 {
+    TOKENS_PER_MESSAGE
+        .saturating_add(saturate(tokenizer.count(&message.content)))
+        .saturating_add(saturate(message.parts.len()).saturating_mul(TOKENS_PER_CONTENT_PART))
+}
+
+/// Converts a count to `u32`, saturating at `u32::MAX`.
+fn saturate(count: usize) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
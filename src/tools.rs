@@ -0,0 +1,214 @@
+//! Tool (function) calling.
+//!
+//! Attach [`Tool`]s to a request with [`LLMRequest::with_tool`](crate::LLMRequest::with_tool).
+//! When the model decides to call one, the response has
+//! [`FinishReason::ToolCalls`](crate::FinishReason::ToolCalls) and the requested calls in
+//! [`LLMResponse::tool_calls`](crate::LLMResponse::tool_calls); its `content` may be empty.
+//!
+//! With the `schemars` feature, `Tool::from_type` derives the argument schema from a Rust
+//! type, and [`ToolCall::arguments_as`] parses the arguments back into it.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Result;
+
+/// A tool the model may call, described by a JSON Schema for its arguments.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use serde_json::json;
+/// use turbine_llm::providers::{LLMProviderTrait, anthropic::AnthropicProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{FinishReason, LLMRequest, Message, Tool};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let weather = Tool::new(
+///     "get_weather",
+///     "Returns the current weather for a city",
+///     json!({"type": "object", "properties": {"city": {"type": "string"}}, "required": ["city"]}),
+/// );
+/// let request = LLMRequest::new("claude-3-5-sonnet-20241022")
+///     .with_tool(weather)
+///     .with_message(Message::user("What's the weather in Paris?"));
+///
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"content":[{"type":"tool_use","id":"toolu_01","name":"get_weather","input":{"city":"Paris"}}],
+///         "stop_reason":"tool_use","usage":{"input_tokens":20,"output_tokens":12}}"#,
+/// )));
+/// let provider = AnthropicProvider::new_with_key("sk-test").with_transport(mock.clone());
+/// let response = provider.send_request(&request).await?;
+///
+/// assert_eq!(response.content, "");
+/// assert_eq!(response.finish_reason, Some(FinishReason::ToolCalls));
+/// let calls = response.tool_calls.unwrap();
+/// assert_eq!(calls[0].id, "toolu_01");
+/// assert_eq!(calls[0].name, "get_weather");
+/// assert_eq!(calls[0].arguments["city"], "Paris");
+///
+/// let body = mock.last_request().unwrap().json_body()?;
+/// assert_eq!(body["tools"][0]["input_schema"]["required"][0], "city");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// The tool name the model uses to call it
+    pub name: String,
+    /// What the tool does and when to use it
+    pub description: String,
+    /// JSON Schema of the tool arguments
+    pub parameters: Value,
+}
+
+impl Tool {
+    /// Creates a tool from its name, description and argument schema.
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+
+    /// Creates a tool whose argument schema is derived from `T`.
+    ///
+    /// Requires the `schemars` feature. See [`schema_for`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use schemars::JsonSchema;
+    /// use serde::Deserialize;
+    /// use turbine_llm::Tool;
+    ///
+    /// /// Arguments of `get_weather`
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct GetWeather {
+    ///     /// City name, e.g. "Paris"
+    ///     city: String,
+    ///     /// Temperature unit
+    ///     unit: Option<String>,
+    /// }
+    ///
+    /// let tool = Tool::from_type::<GetWeather>("get_weather", "Returns the current weather for a city");
+    /// assert_eq!(tool.parameters["type"], "object");
+    /// assert_eq!(tool.parameters["required"][0], "city");
+    /// assert_eq!(tool.parameters["properties"]["city"]["description"], r#"City name, e.g. "Paris""#);
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub fn from_type<T: schemars::JsonSchema>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self::new(name, description, schema_for::<T>())
+    }
+}
+
+/// Returns the JSON Schema of `T` for use as tool parameters or with
+/// [`OutputFormat::JsonSchema`](crate::OutputFormat::JsonSchema).
+///
+/// Subschemas are inlined so the schema has no references, and the `$schema` and `title`
+/// keywords, which some providers reject, are left out. Requires the `schemars` feature.
+#[cfg(feature = "schemars")]
+pub fn schema_for<T: schemars::JsonSchema>() -> Value {
+    let schema = schemars::generate::SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let mut value = Value::from(schema);
+    if let Some(object) = value.as_object_mut() {
+        object.remove("$schema");
+        object.remove("title");
+    }
+    value
+}
+
+/// A tool call requested by the model.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{FinishReason, LLMRequest, Message};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"choices":[{"message":{"content":null,"tool_calls":[{"id":"call_1","type":"function",
+///         "function":{"name":"get_weather","arguments":"{\"city\":\"Paris\"}"}}]},
+///         "finish_reason":"tool_calls"}],"usage":{"prompt_tokens":20,"completion_tokens":12}}"#,
+/// )));
+/// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock);
+///
+/// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Weather in Paris?"));
+/// let response = provider.send_request(&request).await?;
+///
+/// assert_eq!(response.content, "");
+/// assert_eq!(response.finish_reason, Some(FinishReason::ToolCalls));
+/// assert_eq!(response.tool_calls.unwrap()[0].arguments["city"], "Paris");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Identifier linking the call to its result (Gemini has none and uses the tool name)
+    pub id: String,
+    /// Name of the tool to call
+    pub name: String,
+    /// Arguments as a JSON value
+    pub arguments: Value,
+}
+
+impl ToolCall {
+    /// Deserializes the arguments into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::JsonError`](crate::TurbineError::JsonError) if the arguments
+    /// do not match `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    /// use turbine_llm::ToolCall;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct GetWeather {
+    ///     city: String,
+    /// }
+    ///
+    /// let call = ToolCall {
+    ///     id: "call_1".to_string(),
+    ///     name: "get_weather".to_string(),
+    ///     arguments: json!({"city": "Paris"}),
+    /// };
+    /// let args: GetWeather = call.arguments_as()?;
+    /// assert_eq!(args.city, "Paris");
+    /// # Ok::<(), turbine_llm::TurbineError>(())
+    /// ```
+    pub fn arguments_as<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(T::deserialize(&self.arguments)?)
+    }
+}
+
+/// Parses tool arguments that providers encode as a JSON string.
+///
+/// Empty arguments become an empty object; arguments that are not valid JSON are kept as a
+/// string value.
+pub(crate) fn parse_arguments(arguments: &str) -> Value {
+    if arguments.trim().is_empty() {
+        return Value::Object(Default::default());
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
@@ -0,0 +1,522 @@
+//! HTTP transport abstraction used by the providers.
+//!
+//! Providers never talk to `reqwest` directly. Instead they build an [`HttpRequest`] and hand
+//! it to an [`HttpTransport`]. The default transport is [`ReqwestTransport`]; tests can swap in
+//! [`MockTransport`] to return canned responses without a live server.
+
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Result, TurbineError};
+
+/// HTTP method of an [`HttpRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// HTTP `GET`
+    Get,
+    /// HTTP `POST`
+    Post,
+}
+
+/// A transport-agnostic HTTP request built by a provider.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The HTTP method
+    pub method: Method,
+    /// The full request URL
+    pub url: String,
+    /// Request headers as name/value pairs
+    pub headers: Vec<(String, String)>,
+    /// The serialized request body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    /// Creates a `GET` request without a body.
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: Method::Get,
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// Creates a `POST` request with `body` serialized as JSON.
+    pub fn post_json<T: Serialize>(url: impl Into<String>, body: &T) -> Result<Self> {
+        Ok(Self {
+            method: Method::Post,
+            url: url.into(),
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: Some(serde_json::to_vec(body)?),
+        })
+    }
+
+    /// Adds a header to the request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the value of the first header matching `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Parses the request body as JSON.
+    ///
+    /// Mostly useful in tests to assert on the body a provider produced.
+    pub fn json_body(&self) -> Result<serde_json::Value> {
+        let body = self
+            .body
+            .as_deref()
+            .ok_or_else(|| TurbineError::MissingField("request body".to_string()))?;
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+/// A raw HTTP response returned by an [`HttpTransport`].
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// Response headers as name/value pairs
+    pub headers: Vec<(String, String)>,
+    /// The raw response body
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response with the given status code and body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::transport::HttpResponse;
+    ///
+    /// let response = HttpResponse::new(429, r#"{"error": "rate limited"}"#);
+    /// assert!(!response.is_success());
+    /// ```
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Adds a header to the response.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns `true` for 2xx status codes.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Returns the value of the first header matching `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Decodes the body as UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::InvalidResponse`] naming the status, content type and content
+    /// encoding when the body is not valid UTF-8, which usually means a proxy returned
+    /// compressed or mislabeled content.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::TurbineError;
+    /// use turbine_llm::transport::HttpResponse;
+    ///
+    /// let response = HttpResponse::new(200, vec![0x1f, 0x8b, 0x08, 0x00])
+    ///     .with_header("Content-Type", "application/json")
+    ///     .with_header("Content-Encoding", "gzip");
+    ///
+    /// let Err(TurbineError::InvalidResponse(message)) = response.text() else {
+    ///     panic!("expected a decoding error");
+    /// };
+    /// assert!(message.contains("content-type: application/json"));
+    /// assert!(message.contains("content-encoding: gzip"));
+    /// ```
+    pub fn text(&self) -> Result<&str> {
+        std::str::from_utf8(&self.body).map_err(|err| {
+            let mut message = format!(
+                "Response body is not valid UTF-8 (status: {}, content-type: {}",
+                self.status,
+                self.header("content-type").unwrap_or("<none>")
+            );
+            if let Some(encoding) = self.header("content-encoding") {
+                message.push_str(&format!(", content-encoding: {}", encoding));
+            }
+            message.push_str(&format!("): {}", err));
+            TurbineError::InvalidResponse(message)
+        })
+    }
+}
+
+/// A stream of raw body chunks.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>;
+
+/// An HTTP response whose body is read incrementally.
+pub struct HttpStreamResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// Response headers as name/value pairs
+    pub headers: Vec<(String, String)>,
+    /// The response body, chunk by chunk
+    pub body: ByteStream,
+}
+
+impl HttpStreamResponse {
+    /// Returns `true` for 2xx status codes.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Reads the remaining body into a buffered [`HttpResponse`].
+    pub async fn into_response(self) -> Result<HttpResponse> {
+        let body = self
+            .body
+            .try_fold(Vec::new(), |mut body, chunk| async move {
+                body.extend_from_slice(&chunk);
+                Ok(body)
+            })
+            .await?;
+
+        Ok(HttpResponse {
+            status: self.status,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+impl std::fmt::Debug for HttpStreamResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpStreamResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Sends HTTP requests on behalf of a provider.
+///
+/// Implement this trait to route provider traffic through a custom HTTP stack, or use
+/// [`MockTransport`] in tests.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// Sends `request` and returns the full response, whatever its status code.
+    ///
+    /// Only connection-level failures should be reported as errors; non-2xx responses are
+    /// returned as-is so the provider can interpret them.
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+
+    /// Sends `request` and returns the response with a streaming body.
+    ///
+    /// The default implementation buffers the whole response via [`send`](Self::send) and
+    /// yields it as a single chunk. Streaming implementations should read the body lazily,
+    /// only when the returned stream is polled, so that a slow consumer applies backpressure
+    /// instead of the body accumulating in memory.
+    async fn send_streaming(&self, request: HttpRequest) -> Result<HttpStreamResponse> {
+        let response = self.send(request).await?;
+        Ok(HttpStreamResponse {
+            status: response.status,
+            headers: response.headers,
+            body: Box::pin(stream::iter([Ok(response.body)])),
+        })
+    }
+}
+
+/// The default transport, backed by a single reused [`reqwest::Client`].
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a transport with a default `reqwest` client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a transport from a preconfigured `reqwest` client.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    async fn execute(&self, request: HttpRequest) -> Result<reqwest::Response> {
+        let mut builder = match request.method {
+            Method::Get => self.client.get(&request.url),
+            Method::Post => self.client.post(&request.url),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        Ok(builder.send().await?)
+    }
+}
+
+fn response_headers(response: &reqwest::Response) -> Vec<(String, String)> {
+    response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let response = self.execute(request).await?;
+        let status = response.status().as_u16();
+        let headers = response_headers(&response);
+        let body = response.bytes().await?.to_vec();
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    async fn send_streaming(&self, request: HttpRequest) -> Result<HttpStreamResponse> {
+        let response = self.execute(request).await?;
+        let status = response.status().as_u16();
+        let headers = response_headers(&response);
+        let body = response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()).map_err(Into::into));
+
+        Ok(HttpStreamResponse {
+            status,
+            headers,
+            body: Box::pin(body),
+        })
+    }
+}
+
+/// Headers whose values are replaced by [`DebugTransport`].
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "x-api-key", "x-goog-api-key", "api-key"];
+
+/// A transport that prints every request and response to stderr, for interactive debugging.
+///
+/// Prints the method, URL, headers and body of each request, then the status and raw body
+/// of its response; streamed bodies are printed chunk by chunk as they arrive. Values of
+/// authentication headers are printed as `[REDACTED]`. Usually enabled through
+/// [`TurbineClientBuilder::with_debug`](crate::TurbineClientBuilder::with_debug).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+/// use turbine_llm::transport::{DebugTransport, HttpResponse, MockTransport};
+/// use turbine_llm::LLMRequest;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+///     200,
+///     r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#,
+/// )));
+/// let provider = OpenAIProvider::new_with_key("sk-test")
+///     .with_transport(Arc::new(DebugTransport::new(mock.clone())));
+///
+/// // Prints "--> POST https://api.openai.com/v1/chat/completions", the headers with
+/// // "authorization: [REDACTED]", the body, and then the response
+/// let response = provider.send_request(&LLMRequest::quick("gpt-4o-mini", "Hello")).await?;
+/// assert_eq!(response.content, "Hi");
+///
+/// // The request itself is passed on unchanged
+/// assert_eq!(mock.last_request().unwrap().header("authorization"), Some("Bearer sk-test"));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DebugTransport {
+    inner: Arc<dyn HttpTransport>,
+}
+
+impl DebugTransport {
+    /// Wraps `inner`, which sends the requests.
+    pub fn new(inner: Arc<dyn HttpTransport>) -> Self {
+        Self { inner }
+    }
+
+    fn print_request(request: &HttpRequest) {
+        let method = match request.method {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        };
+        eprintln!("--> {method} {}", request.url);
+        for (name, value) in &request.headers {
+            let redacted = SENSITIVE_HEADERS
+                .iter()
+                .any(|sensitive| name.eq_ignore_ascii_case(sensitive));
+            eprintln!("{name}: {}", if redacted { "[REDACTED]" } else { value });
+        }
+        if let Some(body) = &request.body {
+            eprintln!("{}", String::from_utf8_lossy(body));
+        }
+    }
+}
+
+impl std::fmt::Debug for DebugTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugTransport").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for DebugTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        Self::print_request(&request);
+        let result = self.inner.send(request).await;
+        match &result {
+            Ok(response) => {
+                eprintln!("<-- {}", response.status);
+                eprintln!("{}", String::from_utf8_lossy(&response.body));
+            }
+            Err(error) => eprintln!("<-- error: {error}"),
+        }
+        result
+    }
+
+    async fn send_streaming(&self, request: HttpRequest) -> Result<HttpStreamResponse> {
+        Self::print_request(&request);
+        let response = match self.inner.send_streaming(request).await {
+            Ok(response) => response,
+            Err(error) => {
+                eprintln!("<-- error: {error}");
+                return Err(error);
+            }
+        };
+        eprintln!("<-- {} (streaming)", response.status);
+        let body = response.body.inspect(|chunk| match chunk {
+            Ok(bytes) => eprint!("{}", String::from_utf8_lossy(bytes)),
+            Err(error) => eprintln!("<-- stream error: {error}"),
+        });
+        Ok(HttpStreamResponse {
+            body: Box::pin(body),
+            ..response
+        })
+    }
+}
+
+/// An in-memory transport that returns canned responses, for tests.
+///
+/// Responses are returned in the order they were queued, and every request is recorded so
+/// tests can assert on the body and headers a provider produced.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{LLMProviderTrait, openai::OpenAIProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::{LLMRequest, Message, TurbineError};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mock = Arc::new(
+///     MockTransport::new()
+///         .with_response(HttpResponse::new(
+///             200,
+///             r#"{"choices":[{"message":{"content":"Hi!"}}],
+///                 "usage":{"prompt_tokens":5,"completion_tokens":2}}"#,
+///         ))
+///         .with_response(HttpResponse::new(429, "rate limited"))
+///         .with_response(HttpResponse::new(200, "not json")),
+/// );
+/// let provider = OpenAIProvider::new_with_key("sk-test").with_transport(mock.clone());
+/// let request = LLMRequest::new("gpt-4o-mini").with_message(Message::user("Hello"));
+///
+/// let response = provider.send_request(&request).await?;
+/// assert_eq!(response.content, "Hi!");
+/// assert_eq!(response.usage.input_tokens, 5);
+///
+/// let sent = mock.last_request().unwrap();
+/// assert_eq!(sent.header("authorization"), Some("Bearer sk-test"));
+/// assert_eq!(sent.json_body()?["messages"][0]["content"], "Hello");
+///
+/// assert!(matches!(
+///     provider.send_request(&request).await,
+///     Err(TurbineError::RateLimited(_))
+/// ));
+/// assert!(matches!(
+///     provider.send_request(&request).await,
+///     Err(TurbineError::JsonError(_))
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<VecDeque<HttpResponse>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockTransport {
+    /// Creates a mock transport with no queued responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response, builder style.
+    pub fn with_response(self, response: HttpResponse) -> Self {
+        self.push_response(response);
+        self
+    }
+
+    /// Queues a response to be returned by a later request.
+    pub fn push_response(&self, response: HttpResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Returns every request sent so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the most recent request, if any.
+    pub fn last_request(&self) -> Option<HttpRequest> {
+        self.requests.lock().unwrap().last().cloned()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        self.requests.lock().unwrap().push(request);
+        self.responses.lock().unwrap().pop_front().ok_or_else(|| {
+            TurbineError::InvalidResponse("MockTransport has no queued responses".to_string())
+        })
+    }
+}
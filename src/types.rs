@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// - `OpenAI`: Requires `OPENAI_API_KEY`
 /// - `Anthropic`: Requires `ANTHROPIC_API_KEY`
-/// - `Gemini`: Requires `GEMINI_API_KEY`
+/// - `Gemini`: Requires `GEMINI_API_KEY` (or `GOOGLE_API_KEY` as a fallback)
 /// - `Groq`: Requires `GROQ_API_KEY`
 ///
 /// # Example
@@ -21,7 +21,8 @@ use serde::{Deserialize, Serialize};
 /// let provider = Provider::OpenAI;
 /// assert_eq!(provider.env_var(), "OPENAI_API_KEY");
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Provider {
     /// OpenAI (GPT-4, GPT-3.5, etc.)
     OpenAI,
@@ -43,6 +44,91 @@ impl Provider {
         }
     }
 
+    /// Returns the provider whose API key is set in the environment, for applications that
+    /// use a single provider.
+    ///
+    /// The variables are those read by [`api_key_from_env`](Self::api_key_from_env).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] if no provider's key is set, and
+    /// [`TurbineError::AmbiguousProvider`] if keys for several providers are set.
+    ///
+    /// # Example
+    ///
+    /// ```standalone_crate
+    /// use turbine_llm::{Provider, TurbineError};
+    ///
+    /// // SAFETY: no other thread reads the environment yet
+    /// unsafe {
+    ///     for name in ["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GEMINI_API_KEY", "GOOGLE_API_KEY", "GROQ_API_KEY"] {
+    ///         std::env::remove_var(name);
+    ///     }
+    /// }
+    /// assert!(matches!(Provider::from_env(), Err(TurbineError::ApiKeyNotFound(_))));
+    /// assert!(matches!(
+    ///     Provider::OpenAI.api_key_from_env(),
+    ///     Err(TurbineError::ApiKeyNotFound(name)) if name == "OPENAI_API_KEY"
+    /// ));
+    ///
+    /// unsafe { std::env::set_var("ANTHROPIC_API_KEY", "sk-ant") };
+    /// assert_eq!(Provider::from_env()?, Provider::Anthropic);
+    ///
+    /// // GOOGLE_API_KEY counts for Gemini, so two providers are now configured
+    /// unsafe { std::env::set_var("GOOGLE_API_KEY", "google-key") };
+    /// assert!(matches!(
+    ///     Provider::from_env(),
+    ///     Err(TurbineError::AmbiguousProvider { providers }) if providers == [Provider::Anthropic, Provider::Gemini]
+    /// ));
+    ///
+    /// unsafe { std::env::remove_var("ANTHROPIC_API_KEY") };
+    /// assert_eq!(Provider::from_env()?, Provider::Gemini);
+    /// # Ok::<(), TurbineError>(())
+    /// ```
+    pub fn from_env() -> Result<Self, TurbineError> {
+        let all = [
+            Provider::OpenAI,
+            Provider::Anthropic,
+            Provider::Gemini,
+            Provider::Groq,
+        ];
+        let found: Vec<Provider> = all
+            .into_iter()
+            .filter(|provider| provider.api_key_from_env().is_ok())
+            .collect();
+
+        match found.as_slice() {
+            [provider] => Ok(*provider),
+            [] => Err(TurbineError::ApiKeyNotFound(
+                "none of OPENAI_API_KEY, ANTHROPIC_API_KEY, GEMINI_API_KEY (or GOOGLE_API_KEY) \
+                 and GROQ_API_KEY is set"
+                    .to_string(),
+            )),
+            several => Err(TurbineError::AmbiguousProvider {
+                providers: several.to_vec(),
+            }),
+        }
+    }
+
+    /// Reads the provider's API key from the environment.
+    ///
+    /// [`env_var`](Self::env_var) takes precedence. Gemini falls back to `GOOGLE_API_KEY`
+    /// when `GEMINI_API_KEY` is not set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::ApiKeyNotFound`] with the name of the variable if none of the
+    /// variables is set.
+    pub fn api_key_from_env(&self) -> Result<String, TurbineError> {
+        let primary = api_key_from_env_var(self.env_var());
+        match self {
+            Provider::Gemini if primary.is_err() => {
+                api_key_from_env_var("GOOGLE_API_KEY").map_err(|_| primary.unwrap_err())
+            }
+            _ => primary,
+        }
+    }
+
     pub fn base_url(&self) -> &'static str {
         match self {
             Provider::OpenAI => "https://api.openai.com/v1",
@@ -52,10 +138,37 @@ impl Provider {
         }
     }
 
+    /// Returns the base URL of the provider's endpoint that keeps data in `region`, or
+    /// `None` if the provider has no such endpoint.
+    ///
+    /// Only OpenAI offers regional endpoints. For Gemini, use Vertex AI with a regional
+    /// location instead (see
+    /// [`GeminiProvider::new_vertex`](crate::providers::gemini::GeminiProvider::new_vertex)).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use turbine_llm::{Provider, Region};
+    ///
+    /// assert_eq!(Provider::OpenAI.regional_base_url(Region::Eu), Some("https://eu.api.openai.com/v1"));
+    /// assert_eq!(Provider::Anthropic.regional_base_url(Region::Eu), None);
+    /// ```
+    pub fn regional_base_url(&self, region: Region) -> Option<&'static str> {
+        match (self, region) {
+            (Provider::OpenAI, Region::Us) => Some("https://us.api.openai.com/v1"),
+            (Provider::OpenAI, Region::Eu) => Some("https://eu.api.openai.com/v1"),
+            _ => None,
+        }
+    }
+
     /// Parses a provider from a model string in format "provider/model-name".
     ///
+    /// Returns [`TurbineError::UnknownProvider`] for an unsupported prefix and
+    /// [`TurbineError::UnknownModel`] when a name without prefix matches none of the
+    /// patterns below.
+    ///
     /// Supported provider prefixes:
-    /// - "openai/" or "gpt" → OpenAI
+    /// - "openai/", "gpt", or a reasoning model ("o1", "o3", "o4", e.g. "o4-mini") → OpenAI
     /// - "anthropic/" or "claude" → Anthropic
     /// - "google/" or "gemini" → Gemini
     /// - "groq/" or "llama" or "mixtral" → Groq
@@ -63,7 +176,7 @@ impl Provider {
     /// # Example
     ///
     /// ```
-    /// use turbine_llm::Provider;
+    /// use turbine_llm::{Provider, TurbineError};
     ///
     /// let (provider, model) = Provider::from_model_string("google/gemini-flash").unwrap();
     /// assert_eq!(provider, Provider::Gemini);
@@ -72,6 +185,14 @@ impl Provider {
     /// let (provider, model) = Provider::from_model_string("claude-3-5-sonnet").unwrap();
     /// assert_eq!(provider, Provider::Anthropic);
     /// assert_eq!(model, "claude-3-5-sonnet");
+    ///
+    /// let (provider, _) = Provider::from_model_string("o1-mini").unwrap();
+    /// assert_eq!(provider, Provider::OpenAI);
+    ///
+    /// assert!(matches!(
+    ///     Provider::from_model_string("mistral/large"),
+    ///     Err(TurbineError::UnknownProvider { prefix }) if prefix == "mistral"
+    /// ));
     /// ```
     pub fn from_model_string(model: &str) -> Result<(Self, String), TurbineError> {
         // Check for explicit provider prefix (e.g., "openai/gpt-4")
@@ -82,10 +203,9 @@ impl Provider {
                 "google" | "gemini" => Provider::Gemini,
                 "groq" => Provider::Groq,
                 _ => {
-                    return Err(TurbineError::InvalidResponse(format!(
-                        "Unknown provider prefix: {}. Supported: openai, anthropic, google, gemini, groq",
-                        prefix
-                    )));
+                    return Err(TurbineError::UnknownProvider {
+                        prefix: prefix.to_string(),
+                    });
                 }
             };
             return Ok((provider, model_name.to_string()));
@@ -93,7 +213,7 @@ impl Provider {
 
         // Infer provider from model name patterns
         let model_lower = model.to_lowercase();
-        let provider = if model_lower.starts_with("gpt") {
+        let provider = if model_lower.starts_with("gpt") || is_openai_reasoning_model(model) {
             Provider::OpenAI
         } else if model_lower.starts_with("claude") {
             Provider::Anthropic
@@ -102,16 +222,44 @@ impl Provider {
         } else if model_lower.starts_with("llama") || model_lower.starts_with("mixtral") {
             Provider::Groq
         } else {
-            return Err(TurbineError::InvalidResponse(format!(
-                "Cannot infer provider from model name: {}. Use format 'provider/model' (e.g., 'openai/gpt-4')",
-                model
-            )));
+            return Err(TurbineError::UnknownModel {
+                model: model.to_string(),
+            });
         };
 
         Ok((provider, model.to_string()))
     }
 }
 
+/// Returns whether `model` is one of OpenAI's o-series reasoning models (`o1`, `o3`, `o4`
+/// and their variants such as `o4-mini`).
+pub(crate) fn is_openai_reasoning_model(model: &str) -> bool {
+    let model = model.to_lowercase();
+    ["o1", "o3", "o4"].iter().any(|family| {
+        model
+            .strip_prefix(family)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    })
+}
+
+/// Region where a provider processes and stores requests, for data residency.
+///
+/// See [`Provider::regional_base_url`] for which providers support which regions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Region {
+    /// United States
+    Us,
+    /// European Union
+    Eu,
+}
+
+/// Reads an API key from the environment variable `name`, for keys stored under a custom
+/// name instead of [`Provider::env_var`].
+pub(crate) fn api_key_from_env_var(name: &str) -> Result<String, TurbineError> {
+    std::env::var(name).map_err(|_| TurbineError::ApiKeyNotFound(name.to_string()))
+}
+
 /// Output format for LLM responses.
 ///
 /// Specifies whether the response should be plain text or structured JSON.
@@ -124,11 +272,74 @@ impl Provider {
 /// let request = LLMRequest::new("gpt-4o-mini")
 ///     .with_output_format(OutputFormat::Json);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum OutputFormat {
     /// Plain text response (default)
     #[default]
     Text,
     /// Structured JSON response
     Json,
+    /// JSON response matching a JSON Schema
+    ///
+    /// Gemini enforces the schema through `responseJsonSchema`; other providers treat this
+    /// like [`OutputFormat::Json`].
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use serde_json::json;
+    /// use turbine_llm::providers::{LLMProviderTrait, gemini::GeminiProvider};
+    /// use turbine_llm::transport::{HttpResponse, MockTransport};
+    /// use turbine_llm::{LLMRequest, OutputFormat};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = Arc::new(MockTransport::new().with_response(HttpResponse::new(
+    ///     200,
+    ///     r#"{"candidates":[{"content":{"parts":[{"text":"{\"city\":\"Paris\"}"}]}}],
+    ///         "usageMetadata":{"promptTokenCount":9,"candidatesTokenCount":5}}"#,
+    /// )));
+    /// let provider = GeminiProvider::new_with_key("sk-test").with_transport(mock.clone());
+    ///
+    /// let schema = json!({
+    ///     "type": "object",
+    ///     "properties": {"city": {"type": "string"}},
+    ///     "required": ["city"]
+    /// });
+    /// let request = LLMRequest::quick("gemini-2.0-flash", "Where is the Louvre?")
+    ///     .with_output_format(OutputFormat::JsonSchema(schema.clone()));
+    /// provider.send_request(&request).await?;
+    ///
+    /// let config = &mock.last_request().unwrap().json_body()?["generationConfig"];
+    /// assert_eq!(config["responseMimeType"], "application/json");
+    /// assert_eq!(config["responseJsonSchema"], schema);
+    /// # Ok(())
+    /// # }
+    /// ```
+    JsonSchema(serde_json::Value),
+}
+
+impl OutputFormat {
+    /// Returns whether the response is JSON, with or without a schema.
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::JsonSchema(_))
+    }
+}
+
+/// Why the model stopped generating.
+///
+/// Provider-specific reasons are normalized to these variants; anything unrecognized is
+/// kept verbatim in [`FinishReason::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// The model finished its answer or hit a stop sequence
+    Stop,
+    /// Generation was cut off by the token limit
+    Length,
+    /// The model stopped to call a tool
+    ToolCalls,
+    /// Output was blocked or filtered by the provider
+    ContentFilter,
+    /// A provider-specific reason not covered above
+    Other(String),
 }
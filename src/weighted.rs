@@ -0,0 +1,199 @@
+//! Weighted random selection between clients.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, PoisonError};
+
+use crate::{
+    client::TurbineClient,
+    error::{Result, TurbineError},
+    models::{LLMRequest, LLMResponse},
+    types::Provider,
+};
+
+/// Spreads requests across several clients in proportion to their weights.
+///
+/// Each call picks one client at random, so a client with weight 3 serves about three times
+/// as many requests as one with weight 1. This is meant for load tests and gradual rollouts;
+/// a failed request is not retried on another client. Use [`with_seed`](Self::with_seed) for
+/// a reproducible sequence of picks.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use turbine_llm::providers::{anthropic::AnthropicProvider, openai::OpenAIProvider};
+/// use turbine_llm::transport::{HttpResponse, MockTransport};
+/// use turbine_llm::weighted::WeightedClient;
+/// use turbine_llm::{LLMRequest, Provider, TurbineClient};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let openai = Arc::new(MockTransport::new());
+/// let anthropic = Arc::new(MockTransport::new());
+/// for _ in 0..20 {
+///     openai.push_response(HttpResponse::new(
+///         200,
+///         r#"{"choices":[{"message":{"content":"Hi"}}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#,
+///     ));
+///     anthropic.push_response(HttpResponse::new(
+///         200,
+///         r#"{"content":[{"type":"text","text":"Hi"}],"usage":{"input_tokens":1,"output_tokens":1}}"#,
+///     ));
+/// }
+///
+/// let client = WeightedClient::new()
+///     .with_client(
+///         TurbineClient::from_provider(OpenAIProvider::new_with_key("sk-test").with_transport(openai)),
+///         3,
+///     )
+///     .with_client(
+///         TurbineClient::from_provider(AnthropicProvider::new_with_key("sk-test").with_transport(anthropic)),
+///         1,
+///     )
+///     .with_seed(42);
+///
+/// let mut served = Vec::new();
+/// let mut openai_count = 0;
+/// for _ in 0..20 {
+///     let response = client.send_request(&LLMRequest::quick("model", "Hi")).await?;
+///     served.push(response.index);
+///     if response.provider == Some(Provider::OpenAI) {
+///         openai_count += 1;
+///     }
+/// }
+/// assert!(openai_count > 10);
+///
+/// // The same seed picks the same clients
+/// let replay = WeightedClient::new()
+///     .with_client(TurbineClient::from_model_with_key("openai/gpt-4o-mini", "sk-test")?, 3)
+///     .with_client(TurbineClient::from_model_with_key("anthropic/claude-3-5-haiku-20241022", "sk-test")?, 1)
+///     .with_seed(42);
+/// let picks: Vec<_> = (0..20).map(|_| replay.pick().unwrap()).collect();
+/// assert_eq!(picks, served);
+/// # Ok(())
+/// # }
+/// ```
+pub struct WeightedClient {
+    clients: Vec<(TurbineClient, u32)>,
+    rng: Mutex<SplitMix64>,
+}
+
+/// A response together with the client that produced it.
+#[derive(Debug, Clone)]
+pub struct WeightedResponse {
+    /// Index of the client that served the request, in the order clients were added
+    pub index: usize,
+    /// Provider of that client, if known
+    pub provider: Option<Provider>,
+    /// The response
+    pub response: LLMResponse,
+}
+
+impl WeightedClient {
+    /// Creates an empty client with a randomly seeded generator.
+    pub fn new() -> Self {
+        Self {
+            clients: Vec::new(),
+            rng: Mutex::new(SplitMix64(RandomState::new().hash_one(0u8))),
+        }
+    }
+
+    /// Adds a client that serves `weight` parts of the traffic. A weight of 0 disables it.
+    pub fn with_client(mut self, client: TurbineClient, weight: u32) -> Self {
+        self.clients.push((client, weight));
+        self
+    }
+
+    /// Seeds the generator so that the sequence of picks is reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(SplitMix64(seed));
+        self
+    }
+
+    /// Picks the index of the client for the next request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TurbineError::InvalidRequest`] if no client has a weight above 0.
+    pub fn pick(&self) -> Result<usize> {
+        let total: u64 = self
+            .clients
+            .iter()
+            .map(|(_, weight)| u64::from(*weight))
+            .sum();
+        if total == 0 {
+            return Err(TurbineError::InvalidRequest(
+                "WeightedClient has no client with a weight above 0".to_string(),
+            ));
+        }
+
+        let mut target = self
+            .rng
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .next()
+            % total;
+        for (index, (_, weight)) in self.clients.iter().enumerate() {
+            let weight = u64::from(*weight);
+            if target < weight {
+                return Ok(index);
+            }
+            target -= weight;
+        }
+        unreachable!("target is below the total weight")
+    }
+
+    /// Sends a request through a randomly picked client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no client can be picked (see [`pick`](Self::pick)) or the picked
+    /// client's request fails.
+    pub async fn send_request(&self, request: &LLMRequest) -> Result<WeightedResponse> {
+        let index = self.pick()?;
+        let client = &self.clients[index].0;
+        let response = client.send_request(request).await?;
+        Ok(WeightedResponse {
+            index,
+            provider: client.provider_kind(),
+            response,
+        })
+    }
+
+    /// Sends a prompt to the default model of a randomly picked client.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`send_request`](Self::send_request); also fails if the picked client has no
+    /// default model.
+    pub async fn send(&self, message: &str) -> Result<WeightedResponse> {
+        let index = self.pick()?;
+        let client = &self.clients[index].0;
+        let response = client.send(message).await?;
+        Ok(WeightedResponse {
+            index,
+            provider: client.provider_kind(),
+            response,
+        })
+    }
+}
+
+impl Default for WeightedClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The SplitMix64 generator: small, fast, and good enough to spread traffic.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}